@@ -0,0 +1,53 @@
+// ------------------------------------------------------------
+// Bezel: a frame drawn behind the card
+// ------------------------------------------------------------
+
+@group(0) @binding(0)
+var frame_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var frame_sampler: sampler;
+
+// placed the same way as the card's quad in shaders.wgsl
+struct Bezel {
+    quad_scale: vec2<f32>,
+    quad_offset: vec2<f32>,
+    tilt: vec2<f32>,   // rotation (radians), window aspect ratio
+    _pad: vec2<f32>,
+    color: vec4<f32>,
+}
+
+@group(0) @binding(2)
+var<uniform> bezel: Bezel;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// two triangles making a quad, from the vertex index
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0), vec2<f32>(-1.0, -1.0),
+    );
+    let corner = corners[index];
+
+    // rotate in window pixel proportions so the quad doesn't shear
+    var p = corner * bezel.quad_scale * vec2<f32>(bezel.tilt.y, 1.0);
+    let c = cos(bezel.tilt.x);
+    let s = sin(bezel.tilt.x);
+    p = vec2<f32>(p.x * c - p.y * s, p.x * s + p.y * c) / vec2<f32>(bezel.tilt.y, 1.0);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(p + bezel.quad_offset, 0.0, 1.0);
+    out.uv = vec2<f32>(corner.x * 0.5 + 0.5, 0.5 - corner.y * 0.5);
+    return out;
+}
+
+// the texture is plain white unless one was supplied, so this is just the colour by default
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(frame_texture, frame_sampler, in.uv) * bezel.color;
+}
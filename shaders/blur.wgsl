@@ -0,0 +1,54 @@
+// ------------------------------------------------------------
+// Blur: one direction of a separable gaussian
+// ------------------------------------------------------------
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+struct Blur {
+    texel_step: vec2<f32>,   // one texel along the blur direction, in uv
+    radius: f32,             // in texels, sigma is half of this
+    _pad: f32,
+}
+
+@group(0) @binding(2)
+var<uniform> blur: Blur;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// one triangle that covers the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let reach = i32(ceil(blur.radius));
+    let sigma = max(blur.radius * 0.5, 0.001);
+
+    // weight colour by alpha so transparent pixels don't darken the edges
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -reach; i <= reach; i++) {
+        let x = f32(i);
+        let weight = exp(-x * x / (2.0 * sigma * sigma));
+        let texel = textureSampleLevel(source, source_sampler, in.uv + blur.texel_step * x, 0.0);
+        sum += vec4<f32>(texel.rgb * texel.a, texel.a) * weight;
+        total += weight;
+    }
+
+    let alpha = sum.a / total;
+    let rgb = select(vec3<f32>(0.0), sum.rgb / max(sum.a, 0.0001), sum.a > 0.0);
+    return vec4<f32>(rgb, alpha);
+}
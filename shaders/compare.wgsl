@@ -0,0 +1,70 @@
+// ------------------------------------------------------------
+// Compare: the live render against a reference image
+// ------------------------------------------------------------
+
+@group(0) @binding(0)
+var live_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var reference_texture: texture_2d<f32>;
+
+@group(0) @binding(2)
+var compare_sampler: sampler;
+
+// where a quad goes in the window and which part of its texture it shows
+struct Placement {
+    quad_scale: vec2<f32>,
+    quad_offset: vec2<f32>,
+    uv_offset: vec2<f32>,
+    uv_scale: vec2<f32>,
+}
+
+struct Compare {
+    live: Placement,
+    reference: Placement,
+    mode: u32,   // 0 = side by side, 1 = difference
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+}
+
+@group(0) @binding(3)
+var<uniform> compare: Compare;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) which: u32,
+};
+
+// two triangles making a quad from the vertex index, instance 0 is the live render and 1 the reference
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, @builtin(instance_index) instance: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0), vec2<f32>(-1.0, -1.0),
+    );
+    let corner = corners[index];
+    var placement = compare.live;
+    if (instance == 1u) {
+        placement = compare.reference;
+    }
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(corner * placement.quad_scale + placement.quad_offset, 0.0, 1.0);
+    out.uv = placement.uv_offset + vec2<f32>(corner.x * 0.5 + 0.5, 0.5 - corner.y * 0.5) * placement.uv_scale;
+    out.which = instance;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let live = textureSample(live_texture, compare_sampler, in.uv);
+    let reference = textureSample(reference_texture, compare_sampler, in.uv);
+
+    // the reference is stretched over the same area as the live render, so the same uv lines them up
+    if (compare.mode == 1u) {
+        return vec4<f32>(abs(live.rgb - reference.rgb), max(live.a, reference.a));
+    }
+    return select(live, reference, in.which == 1u);
+}
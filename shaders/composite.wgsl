@@ -0,0 +1,87 @@
+// ------------------------------------------------------------
+// Composite: resample the scaled render onto the window
+// ------------------------------------------------------------
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+struct Composite {
+    dest_size: vec2<f32>,
+    filter_mode: u32,   // 0 = bilinear, 1 = tent, 2 = lanczos
+    _pad: u32,
+}
+
+@group(0) @binding(2)
+var<uniform> composite: Composite;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// one triangle that covers the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+const PI: f32 = 3.14159265;
+
+// furthest any filter reaches in source texels, keeps the loop bounded for big scale factors
+const MAX_RADIUS: f32 = 8.0;
+
+fn sinc(x: f32) -> f32 {
+    if (abs(x) < 1e-4) {
+        return 1.0;
+    }
+    return sin(PI * x) / (PI * x);
+}
+
+// filter weight at distance `x`, measured in destination pixels
+fn weight(x: f32) -> f32 {
+    let ax = abs(x);
+    if (composite.filter_mode == 1u) {
+        return max(1.0 - ax, 0.0);
+    }
+    if (ax >= 2.0) {
+        return 0.0;
+    }
+    return sinc(x) * sinc(x / 2.0);
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    if (composite.filter_mode == 0u) {
+        return textureSample(source, source_sampler, uv);
+    }
+
+    // how many source texels each destination pixel covers, at least 1 so upscaling still filters
+    let source_size = vec2<f32>(textureDimensions(source));
+    let scale = max(source_size / composite.dest_size, vec2<f32>(1.0));
+    let support = select(2.0, 1.0, composite.filter_mode == 1u);
+    let radius = min(ceil(support * scale), vec2<f32>(MAX_RADIUS));
+
+    let centre = uv * source_size - 0.5;
+    let base = floor(centre);
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var y = -radius.y; y <= radius.y + 1.0; y += 1.0) {
+        for (var x = -radius.x; x <= radius.x + 1.0; x += 1.0) {
+            let texel = base + vec2<f32>(x, y);
+            let d = (texel - centre) / scale;
+            let w = weight(d.x) * weight(d.y);
+            let coord = clamp(vec2<i32>(texel), vec2<i32>(0), vec2<i32>(source_size) - 1);
+            sum += textureLoad(source, coord, 0) * w;
+            total += w;
+        }
+    }
+
+    return sum / max(total, 1e-4);
+}
@@ -0,0 +1,65 @@
+// ------------------------------------------------------------
+// Glow: a soft halo around the card's rounded silhouette
+// ------------------------------------------------------------
+
+// placed the same way as the card's quad in shaders.wgsl, but grown by `extent` on every side
+struct Glow {
+    quad_scale: vec2<f32>,
+    quad_offset: vec2<f32>,
+    tilt: vec2<f32>,   // rotation (radians), window aspect ratio
+    size: vec2<f32>,   // the silhouette in image pixels
+    color: vec4<f32>,
+    extent: f32,       // how far the glow reaches past the silhouette, in image pixels
+    radius: f32,       // corner radius of the silhouette, in image pixels
+    strength: f32,
+    _pad: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> glow: Glow;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// two triangles making a quad, from the vertex index
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0), vec2<f32>(-1.0, -1.0),
+    );
+    let corner = corners[index];
+
+    // rotate in window pixel proportions so the quad doesn't shear
+    var p = corner * glow.quad_scale * vec2<f32>(glow.tilt.y, 1.0);
+    let c = cos(glow.tilt.x);
+    let s = sin(glow.tilt.x);
+    p = vec2<f32>(p.x * c - p.y * s, p.x * s + p.y * c) / vec2<f32>(glow.tilt.y, 1.0);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(p + glow.quad_offset, 0.0, 1.0);
+    out.uv = vec2<f32>(corner.x * 0.5 + 0.5, 0.5 - corner.y * 0.5);
+    return out;
+}
+
+// signed distance from a rounded rectangle centred on the origin, negative inside
+fn rounded_rect(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let r = min(radius, min(half_size.x, half_size.y));
+    let q = abs(p) - half_size + vec2<f32>(r);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+// fades out exponentially from the edge of the silhouette, and to nothing by the edge of the quad
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = (in.uv - 0.5) * (glow.size + vec2<f32>(2.0 * glow.extent));
+    let d = rounded_rect(p, glow.size * 0.5, glow.radius);
+
+    let outside = clamp(d + 0.5, 0.0, 1.0);
+    let falloff = exp(-max(d, 0.0) * 3.0 / glow.extent);
+    let edge_fade = 1.0 - smoothstep(glow.extent * 0.7, glow.extent, d);
+    let alpha = clamp(glow.strength * outside * falloff * edge_fade, 0.0, 1.0);
+    return vec4<f32>(glow.color.rgb, alpha);
+}
@@ -0,0 +1,40 @@
+// ------------------------------------------------------------
+// Mockup background, drawn behind the card in --mockup mode
+// ------------------------------------------------------------
+
+struct Background {
+    time: f32,
+    aspect: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> bg: Background;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// one triangle that covers the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// slowly swirling two tone gradient, loosely like the game's menu background
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let p = (uv - 0.5) * vec2<f32>(bg.aspect, 1.0);
+    let angle = atan2(p.y, p.x) + length(p) * 3.0 - bg.time * 0.2;
+    let swirl = 0.5 + 0.5 * sin(angle * 2.0 + sin(bg.time * 0.3) * 2.0);
+
+    let red = vec3<f32>(0.85, 0.22, 0.2);
+    let blue = vec3<f32>(0.1, 0.35, 0.55);
+    let vignette = 1.0 - 0.6 * dot(p, p);
+
+    return vec4<f32>(mix(blue, red, swirl) * vignette, 1.0);
+}
@@ -15,11 +15,57 @@ struct Params {
     artifact_amplifier: f32,
     crt_amount_adjusted: f32,
     bloom_fac: f32,
+    crt_curvature: f32,
+    chromatic_aberration: f32,   // red/blue split in texels
+    component_mask: u32,         // which parts of the effect are on, see the COMPONENT_ bits
+    mask_strength: f32,          // how much mask_texture limits the effect, 0 ignores it
+    corner_radius: f32,          // rounds off the card's corners, in image pixels
+    scanline_offset: f32,        // phase shift of the scanlines, as a fraction of their spacing
+    scanline_overlay: u32,       // 1 to highlight the scanlines against the texel rows
+    frame: u32,                  // counts up once per frame drawn
+    dither: u32,                 // one of the DITHER_ modes
+    global_opacity: f32,         // scales the final alpha, 1 is fully opaque
+    uv_test: u32,                // 1 to shade a uv test pattern instead of the image
+}
+
+// values of params.dither, these must match Dither in render.rs
+const DITHER_STATIC: u32 = 1u;
+const DITHER_TEMPORAL: u32 = 2u;
+
+// bits of params.component_mask, these must match COMPONENTS in render.rs
+const COMPONENT_FLICKER: u32 = 1u;
+const COMPONENT_BIAS: u32 = 2u;
+const COMPONENT_CONTRAST: u32 = 4u;
+const COMPONENT_CURVATURE: u32 = 8u;
+const COMPONENT_ABERRATION: u32 = 16u;
+
+fn enabled(component: u32) -> bool {
+    return (params.component_mask & component) != 0u;
 }
 
 @group(0) @binding(2)
 var<uniform> params: Params;
 
+// Quad placement + texture crop (group 0 → binding 3)
+struct View {
+    quad_scale: vec2<f32>,
+    quad_offset: vec2<f32>,
+    uv_offset: vec2<f32>,
+    uv_scale: vec2<f32>,
+    tilt: vec2<f32>,   // rotation (radians), window aspect ratio
+}
+
+@group(0) @binding(3)
+var<uniform> view: View;
+
+// Blurred copy of the texture (group 0 → binding 4), the same as my_texture unless --pre-blur is set
+@group(0) @binding(4)
+var blurred_texture: texture_2d<f32>;
+
+// Where the effect applies (group 0 → binding 5), white everywhere unless --mask is set
+@group(0) @binding(5)
+var mask_texture: texture_2d<f32>;
+
 
 // ------------------------------------------------------------
 // Vertex stage
@@ -28,6 +74,7 @@ var<uniform> params: Params;
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) uv: vec2<f32>,
+    @location(1) local_uv: vec2<f32>,   // 0-1 across the quad, before cropping
 };
 
 @vertex
@@ -36,8 +83,15 @@ fn vs_main(
     @location(1) uv: vec2<f32>,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.position = vec4<f32>(position, 0.0, 1.0);
-    out.uv = uv;
+    // rotate in window pixel proportions so the quad doesn't shear
+    var p = position * view.quad_scale * vec2<f32>(view.tilt.y, 1.0);
+    let c = cos(view.tilt.x);
+    let s = sin(view.tilt.x);
+    p = vec2<f32>(p.x * c - p.y * s, p.x * s + p.y * c) / vec2<f32>(view.tilt.y, 1.0);
+
+    out.position = vec4<f32>(p + view.quad_offset, 0.0, 1.0);
+    out.uv = view.uv_offset + uv * view.uv_scale;
+    out.local_uv = uv;
     return out;
 }
 
@@ -46,6 +100,11 @@ fn vs_main(
 // Effect function (CRT / artifact logic)
 // ------------------------------------------------------------
 
+// whether the scanline (flicker) band covers texture row `y`
+fn scanline(y: f32) -> bool {
+    return sin(params.time + y * 200.0 + params.scanline_offset * 6.2831853) > 0.85;
+}
+
 fn apply_effects(
     tc: vec2<f32>,
     offset_l: f32,
@@ -56,7 +115,7 @@ fn apply_effects(
     var color = rgb_result;
 
     // ----- Flicker sinus effect -----
-    if (sin(params.time + tc.y * 200.0) > 0.85) {
+    if (enabled(COMPONENT_FLICKER) && scanline(tc.y)) {
         if (offset_l < 0.99 && offset_l > 0.01) {
             color.r = color.g * 1.5;
         }
@@ -69,14 +128,21 @@ fn apply_effects(
     let bias = 0.55 - 0.02 * (params.artifact_amplifier - 1.0 -
                params.crt_amount_adjusted * params.bloom_fac * 0.7);
 
-    color = color - vec3<f32>(bias);
+    if (enabled(COMPONENT_BIAS)) {
+        color = color - vec3<f32>(bias);
+    } else {
+        // still centre on the offset added back at the end
+        color = color - vec3<f32>(0.5);
+    }
 
     // ----- Multiply brightness/contrast -----
     let brightness =
         (1.0 + 0.075 +
         params.crt_amount_adjusted * (0.012 - params.bloom_fac * 0.12));
 
-    color = color * brightness;
+    if (enabled(COMPONENT_CONTRAST)) {
+        color = color * brightness;
+    }
 
     // ----- Add constant offset -----
     color = color + vec3<f32>(0.5);
@@ -85,18 +151,61 @@ fn apply_effects(
 }
 
 
+// ------------------------------------------------------------
+// CRT curvature
+// ------------------------------------------------------------
+
+// bend quad coordinates outwards like the glass of a CRT, 0 leaves them flat
+fn curve(local_uv: vec2<f32>) -> vec2<f32> {
+    if (!enabled(COMPONENT_CURVATURE)) {
+        return local_uv;
+    }
+    let centred = local_uv * 2.0 - 1.0;
+    let bent = centred * (1.0 + params.crt_curvature * 0.25 * dot(centred, centred));
+    return bent * 0.5 + 0.5;
+}
+
+
 // ------------------------------------------------------------
 // Fragment stage
 // ------------------------------------------------------------
 
-@fragment
-fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+// a checker of 8x8 texel squares over a red/green gradient of the texture coordinates, so the quad, curvature
+// and view crop can be checked without depending on any particular image
+fn uv_test(uv: vec2<f32>) -> vec4<f32> {
+    let cell = floor(uv * vec2<f32>(textureDimensions(my_texture)) / 8.0);
+    let checker = select(0.4, 1.0, fract((cell.x + cell.y) * 0.5) < 0.25);
+    return vec4<f32>(vec3<f32>(uv, 0.25) * checker, 1.0);
+}
+
+// the image at `uv`, or the uv test pattern in its place with --uv-test
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+    if (params.uv_test != 0u) {
+        return uv_test(uv);
+    }
+    return textureSample(my_texture, my_sampler, uv);
+}
+
+fn shade(in: VertexOutput) -> vec4<f32> {
+    // Apply curvature in quad space, then crop into the texture
+    let local_uv = curve(in.local_uv);
+    let uv = view.uv_offset + local_uv * view.uv_scale;
+
+    // anything bent off the edge of the screen is blank
+    let inside = all(local_uv >= vec2<f32>(0.0)) && all(local_uv <= vec2<f32>(1.0));
+
     // Sample texture
-    let tex = textureSample(my_texture, my_sampler, uv);
+    let tex = sample_source(uv);
 
     // Convert sampled color to mutable vec3
     var rgb = tex.rgb;
 
+    // Chromatic aberration: pull red one way and blue the other, 0 leaves them lined up
+    let ca_amount = select(0.0, params.chromatic_aberration, enabled(COMPONENT_ABERRATION));
+    let ca = vec2<f32>(ca_amount / f32(textureDimensions(my_texture).x), 0.0);
+    rgb.r = sample_source(uv + ca).r;
+    rgb.b = sample_source(uv - ca).b;
+
     // --------------------------------------------------------
     // Your original shader expects these values:
     // offset_l and offset_r can be anything (horizontal artifacts)
@@ -107,6 +216,108 @@ fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
 
     // Apply CRT-style effects
     let result = apply_effects(uv, offset_l, offset_r, rgb);
+    let shaded = vec4<f32>(result, tex.a) * select(0.0, 1.0, inside);
+
+    // Show the untouched image wherever the mask is black
+    let original = sample_source(in.uv);
+    let mask = textureSample(mask_texture, my_sampler, in.uv).r;
+    let masked = mix(original, shaded, mix(1.0, mask, params.mask_strength));
+
+    // Round off the corners, measured in image pixels so the radius matches the art
+    if (params.corner_radius <= 0.0) {
+        return masked;
+    }
+    let size = view.uv_scale * vec2<f32>(textureDimensions(my_texture));
+    let half_size = size * 0.5;
+    let r = min(params.corner_radius, min(half_size.x, half_size.y));
+    let q = abs((local_uv - 0.5) * size) - half_size + vec2<f32>(r);
+    let d = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+    // only alpha is faded, the colour stays as it is since it's blended (and exported) as straight alpha
+    let coverage = clamp(0.5 - d, 0.0, 1.0);
+    return vec4<f32>(masked.rgb, masked.a * coverage);
+}
 
-    return vec4<f32>(result, tex.a);
+// ------------------------------------------------------------
+// Scanline alignment overlay
+// ------------------------------------------------------------
+
+// tint wherever the scanlines fall and outline every texel row, so it's easy to see how they line up
+fn scanline_overlay(in: VertexOutput, color: vec4<f32>) -> vec4<f32> {
+    if (params.scanline_overlay == 0u) {
+        return color;
+    }
+    let uv = view.uv_offset + curve(in.local_uv) * view.uv_scale;
+    let row = uv.y * f32(textureDimensions(my_texture).y);
+
+    // lines only once rows are a few pixels tall, otherwise they'd cover everything
+    let edge = abs(fract(row + 0.5) - 0.5) / max(fwidth(row), 1e-5);
+    let line = select(0.0, 0.6, edge < 1.0 && fwidth(row) < 0.25);
+    let band = select(0.0, 0.45, scanline(uv.y));
+
+    var rgb = mix(color.rgb, vec3<f32>(1.0, 0.0, 1.0), band);
+    rgb = mix(rgb, vec3<f32>(0.0, 1.0, 1.0), line);
+    return vec4<f32>(rgb, max(color.a, max(band, line)));
+}
+
+// ------------------------------------------------------------
+// Dithering
+// ------------------------------------------------------------
+
+fn srgb_encode(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+fn srgb_decode(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+// nudge each pixel by up to half an 8 bit step so gradients don't band, temporal dithering moves the
+// pattern on every frame so it averages out in motion. the step is measured after srgb encoding, which
+// is where the output gets quantised
+fn dither(position: vec2<f32>, color: vec4<f32>) -> vec4<f32> {
+    if (params.dither != DITHER_STATIC && params.dither != DITHER_TEMPORAL) {
+        return color;
+    }
+    var p = position;
+    if (params.dither == DITHER_TEMPORAL) {
+        p += 5.588238 * f32(params.frame % 64u);
+    }
+    // interleaved gradient noise
+    let noise = fract(52.9829189 * fract(dot(p, vec2<f32>(0.06711056, 0.00583715))));
+    let encoded = srgb_encode(max(color.rgb, vec3<f32>(0.0))) + (noise - 0.5) / 255.0;
+    return vec4<f32>(srgb_decode(max(encoded, vec3<f32>(0.0))), color.a);
+}
+
+// dim the whole card by params.global_opacity, for showing it semi-transparent over something else
+fn fade(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(color.rgb, color.a * params.global_opacity);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return fade(dither(in.position.xy, scanline_overlay(in, shade(in))));
+}
+
+
+// ------------------------------------------------------------
+// Debug output (--mrt-debug)
+// ------------------------------------------------------------
+
+// write whatever intermediate values are useful to location 1, the app shows or exports it on request
+struct DebugOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) debug: vec4<f32>,
+};
+
+@fragment
+fn fs_debug(in: VertexOutput) -> DebugOutput {
+    var out: DebugOutput;
+    out.color = fade(dither(in.position.xy, scanline_overlay(in, shade(in))));
+
+    // red/green: curved quad coordinates, blue: where the flicker band is
+    let local_uv = curve(in.local_uv);
+    let uv = view.uv_offset + local_uv * view.uv_scale;
+    let flicker = select(0.0, 1.0, scanline(uv.y));
+    out.debug = vec4<f32>(local_uv, flicker, 1.0);
+    return out;
 }
@@ -0,0 +1,47 @@
+// ------------------------------------------------------------
+// Tile preview: the live render repeated in a grid
+// ------------------------------------------------------------
+
+@group(0) @binding(0)
+var live_texture: texture_2d<f32>;
+
+// repeat addressing, so uvs past 1 wrap back round to the other edge
+@group(0) @binding(1)
+var tile_sampler: sampler;
+
+struct Tile {
+    quad_scale: vec2<f32>,
+    quad_offset: vec2<f32>,
+    tiles: f32,   // how many times the render repeats across and down
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+}
+
+@group(0) @binding(2)
+var<uniform> tile: Tile;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// two triangles making a quad from the vertex index
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0), vec2<f32>(-1.0, -1.0),
+    );
+    let corner = corners[index];
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(corner * tile.quad_scale + tile.quad_offset, 0.0, 1.0);
+    out.uv = vec2<f32>(corner.x * 0.5 + 0.5, 0.5 - corner.y * 0.5) * tile.tiles;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(live_texture, tile_sampler, in.uv);
+}
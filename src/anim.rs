@@ -0,0 +1,131 @@
+use image::{Delay, Frame, RgbaImage};
+
+// save `frames` as a gif that loops forever, showing each for `delay_ms`. gifs only have 256 colours and
+// on/off transparency, so gradients band and soft edges go hard
+pub fn save_gif(frames: &[RgbaImage], delay_ms: u32, path: &str) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(std::io::BufWriter::new(file), 10);
+    encoder.set_repeat(image::codecs::gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+    let delay = Delay::from_numer_denom_ms(delay_ms, 1);
+    encoder
+        .encode_frames(frames.iter().map(|frame| Frame::from_parts(frame.clone(), 0, 0, delay)))
+        .map_err(|e| e.to_string())
+}
+
+// save `frames` as a lossless webp that loops forever, showing each for `delay_ms`. keeps full colour and alpha.
+// the encoder only does still images, so each frame is encoded on its own and wrapped in the animation chunks
+#[cfg(feature = "webp")]
+pub fn save_webp(frames: &[RgbaImage], delay_ms: u32, path: &str) -> Result<(), String> {
+    let (width, height) = frames.first().ok_or("no frames to save")?.dimensions();
+
+    // VP8X: animated with alpha, then the canvas size
+    let mut vp8x = vec![0x02 | 0x10, 0, 0, 0];
+    vp8x.extend_from_slice(&u24(width - 1));
+    vp8x.extend_from_slice(&u24(height - 1));
+    let mut body = b"WEBP".to_vec();
+    push_chunk(&mut body, b"VP8X", &vp8x);
+
+    // ANIM: transparent background, loop forever
+    push_chunk(&mut body, b"ANIM", &[0, 0, 0, 0, 0, 0]);
+
+    for frame in frames {
+        let mut still = Vec::new();
+        image::codecs::webp::WebPEncoder::new_lossless(&mut still)
+            .encode(frame.as_raw(), width, height, image::ExtendedColorType::Rgba8)
+            .map_err(|e| e.to_string())?;
+
+        // ANMF: placed at the origin, full size, no blending with the frame before (bit 1) so alpha stays exact
+        let mut anmf = [u24(0), u24(0), u24(width - 1), u24(height - 1), u24(delay_ms.min(0xFF_FFFF))].concat();
+        anmf.push(0x02);
+        for (id, data) in chunks(&still[12..]) {
+            if id == b"VP8L" || id == b"VP8 " || id == b"ALPH" {
+                push_chunk(&mut anmf, id, data);
+            }
+        }
+        push_chunk(&mut body, b"ANMF", &anmf);
+    }
+
+    let mut out = b"RIFF".to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(&body);
+    std::fs::write(path, out).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+// a 24 bit little endian number, as the webp headers use
+#[cfg(feature = "webp")]
+fn u24(value: u32) -> [u8; 3] {
+    let [a, b, c, _] = value.to_le_bytes();
+    [a, b, c]
+}
+
+// riff chunks are a four character id, the size and the data padded to an even length
+#[cfg(feature = "webp")]
+fn push_chunk(out: &mut Vec<u8>, id: &[u8], data: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+// the chunks in `data`, as (id, contents)
+#[cfg(feature = "webp")]
+fn chunks(mut data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut found = Vec::new();
+    while data.len() >= 8 {
+        let size = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let Some(contents) = data.get(8..8 + size) else {
+            break;
+        };
+        found.push((&data[..4], contents));
+        data = data.get(8 + size + size % 2..).unwrap_or_default();
+    }
+    found
+}
+
+#[cfg(all(test, feature = "webp"))]
+mod tests {
+    use super::*;
+    use image::AnimationDecoder;
+
+    #[test]
+    fn two_frame_webp_has_valid_chunks() {
+        let frames = [RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 128])), RgbaImage::from_pixel(3, 2, image::Rgba([0, 0, 255, 255]))];
+        let path = std::env::temp_dir().join(format!("anim-test-{}.webp", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_webp(&frames, 40, path).unwrap();
+        let data = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        // the riff length covers everything after itself
+        assert_eq!(&data[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize, data.len() - 8);
+        assert_eq!(&data[8..12], b"WEBP");
+
+        // chunk sizes have to add up exactly for chunks() to walk to the end
+        let found = chunks(&data[12..]);
+        let total: usize = found.iter().map(|(_, contents)| 8 + contents.len() + contents.len() % 2).sum();
+        assert_eq!(total, data.len() - 12);
+        let ids: Vec<&[u8]> = found.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [&b"VP8X"[..], b"ANIM", b"ANMF", b"ANMF"]);
+
+        // canvas is stored as size - 1, and each frame carries its delay and an image chunk that fills it
+        let vp8x = found[0].1;
+        assert_eq!((&vp8x[4..7], &vp8x[7..10]), (&u24(2)[..], &u24(1)[..]));
+        for (_, anmf) in &found[2..] {
+            assert_eq!(&anmf[12..15], &u24(40));
+            let inner = chunks(&anmf[16..]);
+            let inner_total: usize = inner.iter().map(|(_, contents)| 8 + contents.len() + contents.len() % 2).sum();
+            assert_eq!(inner_total, anmf.len() - 16);
+            assert_eq!(inner[0].0, b"VP8L");
+        }
+
+        // and a decoder reads back the same frames
+        let decoded = image::codecs::webp::WebPDecoder::new(std::io::Cursor::new(data)).unwrap().into_frames().collect_frames().unwrap();
+        assert_eq!(decoded.len(), 2);
+        for (frame, original) in decoded.iter().zip(&frames) {
+            assert_eq!(frame.buffer(), original);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::render::{Params, SWEEPABLE};
+
+// params changing over the loop, as keyframes that are blended between. saved as csv with a `time` column
+// followed by one column per param, named as in the params json
+pub struct Automation {
+    names: Vec<String>,
+    // (time, one value per name), in time order
+    keys: Vec<(f32, Vec<f32>)>,
+}
+
+impl Automation {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::parse(&source)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut lines = source.lines().map(str::trim).filter(|line| !line.is_empty());
+        let header: Vec<&str> = lines.next().ok_or("empty automation file")?.split(',').map(str::trim).collect();
+        if header.first() != Some(&"time") {
+            return Err(String::from("the first column must be time"));
+        }
+        let names: Vec<String> = header[1..].iter().map(|name| name.to_string()).collect();
+        if let Some(unknown) = names.iter().find(|name| Params::default().value(name).is_none()) {
+            return Err(format!("unknown param {}", unknown));
+        }
+
+        let mut keys = Vec::new();
+        for (number, line) in lines.enumerate() {
+            let values: Result<Vec<f32>, _> = line.split(',').map(|value| value.trim().parse()).collect();
+            let values = values.map_err(|_| format!("row {}: expected numbers", number + 1))?;
+            if values.len() != header.len() {
+                return Err(format!("row {}: expected {} values, found {}", number + 1, header.len(), values.len()));
+            }
+            keys.push((values[0], values[1..].to_vec()));
+        }
+        if keys.is_empty() {
+            return Err(String::from("no keyframes"));
+        }
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(Self { names, keys })
+    }
+
+    // set the automated params for `time`, blending between the keyframes either side of it. before the
+    // first or after the last keyframe it holds that keyframe's values
+    pub fn apply(&self, time: f32, params: &mut Params) {
+        let next = self.keys.partition_point(|(key_time, _)| *key_time <= time);
+        let values: Vec<f32> = match (next.checked_sub(1).map(|i| &self.keys[i]), self.keys.get(next)) {
+            (Some((t0, a)), Some((t1, b))) => {
+                let f = (time - t0) / (t1 - t0);
+                a.iter().zip(b).map(|(a, b)| a + (b - a) * f).collect()
+            }
+            (Some((_, a)), None) => a.clone(),
+            (None, Some((_, b))) => b.clone(),
+            (None, None) => return,
+        };
+        for (name, value) in self.names.iter().zip(values) {
+            if let Some(slot) = params.value_mut(name) {
+                *slot = value;
+            }
+        }
+    }
+}
+
+// logs a keyframe every time the params change, for performing an automation live with the sliders
+#[derive(Default)]
+pub struct Recorder {
+    rows: Vec<String>,
+    last: Option<(f32, Params)>,
+}
+
+// everything that can be automated, time is its own column
+fn recorded_names() -> impl Iterator<Item = &'static str> {
+    SWEEPABLE.iter().map(|&(name, _)| name).filter(|&name| name != "time")
+}
+
+impl Recorder {
+    // note the params at `time` if they've changed since the last keyframe
+    pub fn record(&mut self, time: f32, params: &Params) {
+        let changed = match &self.last {
+            Some((_, last)) => recorded_names().any(|name| last.value(name) != params.value(name)),
+            None => true,
+        };
+        if changed {
+            self.push(time, params);
+        }
+        self.last = Some((time, *params));
+    }
+
+    // the time of the last frame recorded
+    pub fn last_time(&self) -> Option<f32> {
+        self.last.map(|(time, _)| time)
+    }
+
+    fn push(&mut self, time: f32, params: &Params) {
+        let values: Vec<String> = recorded_names().map(|name| params.value(name).unwrap_or_default().to_string()).collect();
+        self.rows.push(format!("{},{}", time, values.join(",")));
+    }
+
+    // write the keyframes as csv for `--automation`, finishing with the last frame so holds at the end are kept
+    pub fn save(mut self, path: &str) -> std::io::Result<usize> {
+        if let Some((time, params)) = self.last {
+            if !self.rows.last().is_some_and(|row| row.starts_with(&format!("{},", time))) {
+                self.push(time, &params);
+            }
+        }
+        let header: Vec<&str> = std::iter::once("time").chain(recorded_names()).collect();
+        let mut out = header.join(",") + "\n";
+        for row in &self.rows {
+            out += row;
+            out += "\n";
+        }
+        std::fs::write(path, out)?;
+        Ok(self.rows.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Automation {
+        Automation::parse(source).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[test]
+    fn blends_between_keyframes() {
+        // rows out of order get sorted, blank lines and spaces are fine
+        let automation = parse("time, bloom_fac, crt_curvature\n\n2, 3.0, 0\n0, 1.0, 1\n");
+        let mut params = Params::default();
+        automation.apply(1.0, &mut params);
+        assert_eq!((params.bloom_fac, params.crt_curvature), (2.0, 0.5));
+
+        // held before the first and after the last keyframe
+        automation.apply(-1.0, &mut params);
+        assert_eq!(params.bloom_fac, 1.0);
+        automation.apply(5.0, &mut params);
+        assert_eq!(params.bloom_fac, 3.0);
+    }
+
+    #[test]
+    fn leaves_other_params_alone() {
+        let mut params = Params { crt_curvature: 0.3, ..Params::default() };
+        parse("time,bloom_fac\n0,2\n").apply(0.0, &mut params);
+        assert_eq!((params.bloom_fac, params.crt_curvature), (2.0, 0.3));
+    }
+
+    #[test]
+    fn rejects_malformed_csv() {
+        for (source, error) in [
+            ("", "empty automation file"),
+            ("bloom_fac,time\n0,0\n", "the first column must be time"),
+            ("time,not_a_param\n0,0\n", "unknown param not_a_param"),
+            ("time,bloom_fac\n", "no keyframes"),
+            ("time,bloom_fac\n0,1\n1,lots\n", "row 2: expected numbers"),
+            ("time,bloom_fac\n0,1,2\n", "row 1: expected 2 values, found 3"),
+            ("time,bloom_fac\n0\n", "row 1: expected 2 values, found 1"),
+        ] {
+            assert_eq!(Automation::parse(source).err().as_deref(), Some(error));
+        }
+    }
+
+    #[test]
+    fn recorder_output_plays_back() {
+        let mut recorder = Recorder::default();
+        let mut params = Params::default();
+        recorder.record(0.0, &params);
+        params.bloom_fac = 4.0;
+        recorder.record(1.0, &params);
+        // unchanged frames aren't keyframes, except the last one so the hold is kept
+        recorder.record(2.0, &params);
+
+        let path = std::env::temp_dir().join(format!("automation-test-{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert_eq!(recorder.save(path).unwrap(), 3);
+        let automation = Automation::load(path);
+        std::fs::remove_file(path).unwrap();
+
+        let automation = automation.unwrap_or_else(|e| panic!("{}", e));
+        let mut played = Params::default();
+        automation.apply(0.5, &mut played);
+        assert!((played.bloom_fac - (Params::default().bloom_fac + 4.0) / 2.0).abs() < 1e-6);
+        automation.apply(2.0, &mut played);
+        assert_eq!(played.bloom_fac, 4.0);
+    }
+}
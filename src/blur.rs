@@ -0,0 +1,192 @@
+use wgpu::util::DeviceExt;
+use crate::render::ScratchTarget;
+
+// biggest radius allowed, keeps the shader's loop bounded
+pub const MAX_RADIUS: f32 = 64.0;
+
+// most times the blur can be run over itself, past this it barely changes but the cost keeps growing
+pub const MAX_ITERATIONS: u32 = 8;
+
+// uniforms for the blur shader, must match `Blur` in blur.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+struct BlurUniform {
+    texel_step: [f32; 2],
+    radius: f32,
+    _pad: f32,
+}
+
+// separable gaussian blur from one texture into another of the same size, shared by anything that
+// needs a blurred copy of something (the pre-blurred input for custom shaders)
+pub struct Blur {
+    // how many times the two passes are repeated, each one spreads the blur wider and smoother
+    pub iterations: u32,
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+
+    // holds the horizontal pass while the vertical one reads it
+    scratch: ScratchTarget,
+}
+
+impl Blur {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("blur_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("blur_bind_group_layout"),
+            entries: &[
+                // binding 0: texture being blurred
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+
+                // binding 1: sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+
+                // binding 2: uniform buffer (Blur)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blur Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/blur.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blur Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // no vertex buffer, the shader makes a fullscreen triangle from the vertex index
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Blur Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Option::from("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Option::from("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            iterations: 1,
+            sampler,
+            bind_group_layout,
+            pipeline,
+            scratch: ScratchTarget::with_usage(format, wgpu::TextureUsages::TEXTURE_BINDING),
+        }
+    }
+
+    // blur `src` into `dst`, both `size` texels, a radius of 0 just copies
+    pub fn run(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &wgpu::TextureView,
+        dst: &wgpu::TextureView,
+        size: (u32, u32),
+        radius: f32,
+    ) {
+        let radius = radius.clamp(0.0, MAX_RADIUS);
+        let scratch = self.scratch.view(device, size).clone();
+        let horizontal = [1.0 / size.0 as f32, 0.0];
+        let vertical = [0.0, 1.0 / size.1 as f32];
+
+        // the first iteration reads the source, the rest ping-pong between dst and the scratch target
+        let passes = (0..self.iterations.clamp(1, MAX_ITERATIONS)).flat_map(|i| {
+            let from = if i == 0 { src } else { dst };
+            [(horizontal, from, &scratch), (vertical, &scratch, dst)]
+        });
+        for (texel_step, from, to) in passes {
+            // each pass gets its own buffer so both can be recorded before anything is submitted
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Blur Buffer"),
+                contents: bytemuck::bytes_of(&BlurUniform { texel_step, radius, _pad: 0.0 }),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("blur_bind_group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(from),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Blur Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: to,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
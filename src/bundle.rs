@@ -0,0 +1,63 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+// image types we'll pick out of a bundle, the same ones the file picker offers
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "bmp"];
+
+// the files pulled out of a bundle, ready to load like any others
+pub struct Bundle {
+    pub image: PathBuf,
+    pub shader: Option<PathBuf>,
+}
+
+// pull `image = <name>` and `shader = <name>` out of a manifest, either can be left out
+fn read_manifest(text: &str) -> (Option<String>, Option<String>) {
+    let (mut image, mut shader) = (None, None);
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "image" => image = Some(value.trim().to_string()),
+            "shader" => shader = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    (image, shader)
+}
+
+// extract a zip into a temp folder and find its image and shader. a `manifest.txt` can name them,
+// otherwise the shader is `shader.wgsl` and the image is the first image file in the archive
+pub fn extract(path: &str) -> Result<Bundle, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open bundle {}: {}", path, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read bundle {}: {}", path, e))?;
+
+    let stem = Path::new(path).file_stem().map_or(String::from("bundle"), |stem| stem.to_string_lossy().to_string());
+    let dir = std::env::temp_dir().join(format!("balatro-shader-sim-{}", stem));
+    archive.extract(&dir).map_err(|e| format!("Failed to extract bundle {}: {}", path, e))?;
+
+    let manifest = std::fs::read_to_string(dir.join("manifest.txt")).unwrap_or_default();
+    let (image, shader) = read_manifest(&manifest);
+
+    // fall back to the conventional names, in the order they're stored in the archive
+    let image = image.or_else(|| {
+        archive.file_names().find(|name| {
+            let extension = Path::new(name).extension().map(|e| e.to_string_lossy().to_lowercase());
+            extension.is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.as_str()))
+        }).map(String::from)
+    });
+    let shader = shader.or_else(|| archive.file_names().find(|name| *name == "shader.wgsl").map(String::from));
+
+    let Some(image) = image else {
+        return Err(format!("Bundle {} doesn't contain an image", path));
+    };
+    let image = dir.join(image);
+    let shader = shader.map(|shader| dir.join(shader));
+    for file in std::iter::once(&image).chain(&shader) {
+        if !file.exists() {
+            return Err(format!("Bundle {} doesn't contain {}", path, file.display()));
+        }
+    }
+
+    Ok(Bundle { image, shader })
+}
@@ -0,0 +1,105 @@
+use std::net::UdpSocket;
+
+// listens for the time from other software over udp, so several viewers (or a vj setup) can stay in step
+pub struct ExternalClock {
+    socket: UdpSocket,
+}
+
+impl ExternalClock {
+    // listen on every interface on `port`
+    pub fn bind(port: u16) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket })
+    }
+
+    // the newest time sent since the last poll, if any. never waits
+    pub fn poll(&mut self) -> Option<f32> {
+        let mut buf = [0u8; 1024];
+        let mut latest = None;
+        while let Ok(len) = self.socket.recv(&mut buf) {
+            match parse_time(&buf[..len]) {
+                Some(time) => latest = Some(time),
+                None => eprintln!("Ignoring clock packet, expected a number or an osc message with one float"),
+            }
+        }
+        latest
+    }
+}
+
+// a packet is either the time as text ("12.5") or an osc message with a single float, double or int
+// argument, the osc address isn't checked so any will do
+pub fn parse_time(packet: &[u8]) -> Option<f32> {
+    if !packet.starts_with(b"/") {
+        return std::str::from_utf8(packet).ok()?.trim().parse().ok();
+    }
+
+    // osc strings end with a nul and are padded out to a multiple of 4 bytes
+    let padded = |start: usize| -> Option<usize> {
+        let len = packet.get(start..)?.iter().position(|&b| b == 0)?;
+        Some(start + (len + 4) / 4 * 4)
+    };
+    let tags_start = padded(0)?;
+    let args_start = padded(tags_start)?;
+    let tags = packet.get(tags_start..args_start)?;
+    let arg = packet.get(args_start..)?;
+
+    match tags.get(..2)? {
+        b",f" => Some(f32::from_be_bytes(arg.get(..4)?.try_into().ok()?)),
+        b",d" => Some(f64::from_be_bytes(arg.get(..8)?.try_into().ok()?) as f32),
+        b",i" => Some(i32::from_be_bytes(arg.get(..4)?.try_into().ok()?) as f32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an osc message with `address`, the type tag and `arg`, padded the way senders do
+    fn osc(address: &str, tag: &str, arg: &[u8]) -> Vec<u8> {
+        let mut packet = Vec::new();
+        for s in [address, tag] {
+            packet.extend_from_slice(s.as_bytes());
+            packet.resize((packet.len() + 4) / 4 * 4, 0);
+        }
+        packet.extend_from_slice(arg);
+        packet
+    }
+
+    #[test]
+    fn reads_text() {
+        assert_eq!(parse_time(b"12.5"), Some(12.5));
+        assert_eq!(parse_time(b" 3\n"), Some(3.0));
+        assert_eq!(parse_time(b"soon"), None);
+        assert_eq!(parse_time(&[0xff, 0xfe]), None);
+    }
+
+    #[test]
+    fn reads_osc_args() {
+        assert_eq!(parse_time(&osc("/t", ",f", &2.5f32.to_be_bytes())), Some(2.5));
+        assert_eq!(parse_time(&osc("/time", ",d", &7.25f64.to_be_bytes())), Some(7.25));
+        assert_eq!(parse_time(&osc("/time/beat", ",i", &(-4i32).to_be_bytes())), Some(-4.0));
+    }
+
+    #[test]
+    fn osc_padding() {
+        // an address that fills 4 bytes exactly still needs a whole word of nuls after it
+        let packet = osc("/abc", ",f", &1.5f32.to_be_bytes());
+        assert_eq!(&packet[..12], b"/abc\0\0\0\0,f\0\0");
+        assert_eq!(parse_time(&packet), Some(1.5));
+
+        // leaving the padding out shifts everything along
+        let mut unpadded = b"/abc\0,f\0\0".to_vec();
+        unpadded.extend_from_slice(&1.5f32.to_be_bytes());
+        assert_ne!(parse_time(&unpadded), Some(1.5));
+    }
+
+    #[test]
+    fn rejects_short_or_unknown_osc() {
+        assert_eq!(parse_time(&osc("/t", ",f", &[0, 0])), None);
+        assert_eq!(parse_time(&osc("/t", ",s", b"one\0")), None);
+        assert_eq!(parse_time(b"/t"), None);
+        assert_eq!(parse_time(b"/t\0\0"), None);
+    }
+}
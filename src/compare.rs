@@ -0,0 +1,320 @@
+use image::RgbaImage;
+use wgpu::util::DeviceExt;
+use crate::view::{self, View, ViewSettings};
+
+// where a quad goes and what part of its texture it shows, must match `Placement` in compare.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+struct Placement {
+    quad_scale: [f32; 2],
+    quad_offset: [f32; 2],
+    uv_offset: [f32; 2],
+    uv_scale: [f32; 2],
+}
+
+impl From<View> for Placement {
+    fn from(view: View) -> Self {
+        Self { quad_scale: view.quad_scale, quad_offset: view.quad_offset, uv_offset: view.uv_offset, uv_scale: view.uv_scale }
+    }
+}
+
+// uniforms for the compare shader, must match `Compare` in compare.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+struct CompareUniform {
+    live: Placement,
+    reference: Placement,
+    mode: u32,
+    _pad: [u32; 3],
+}
+
+// a texture at `size` that can be drawn to and then sampled
+fn create_target(device: &wgpu::Device, label: &str, format: wgpu::TextureFormat, size: (u32, u32)) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: Default::default(),
+    })
+}
+
+// both textures, sampled with the same filtering as the image itself
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    live_view: &wgpu::TextureView,
+    reference_view: &wgpu::TextureView,
+    buffer: &wgpu::Buffer,
+    filter: wgpu::FilterMode,
+) -> wgpu::BindGroup {
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("compare_sampler"),
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        mag_filter: filter,
+        min_filter: filter,
+        ..Default::default()
+    });
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("compare_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(live_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(reference_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+// squeeze a view made for half the window into the left or right half of the whole window
+fn into_half(view: View, right: bool) -> Placement {
+    let shift = if right { 0.5 } else { -0.5 };
+    Placement {
+        quad_scale: [view.quad_scale[0] * 0.5, view.quad_scale[1]],
+        quad_offset: [view.quad_offset[0] * 0.5 + shift, view.quad_offset[1]],
+        ..view.into()
+    }
+}
+
+// shows the live render next to a reference image, or the difference between them, for matching the
+// effect to a screenshot from the game
+pub struct Compare {
+    // show the difference instead of the two side by side
+    pub difference: bool,
+    img_size: (u32, u32),
+    reference_size: (u32, u32),
+    // the reference is another render of the same image, so it's placed exactly like the live one
+    rendered: bool,
+    live_view: wgpu::TextureView,
+    reference_view: wgpu::TextureView,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Compare {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        img_size: (u32, u32),
+        reference: &RgbaImage,
+        filter: wgpu::FilterMode,
+    ) -> Self {
+        let reference_texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("reference_texture"),
+                size: wgpu::Extent3d { width: reference.width(), height: reference.height(), depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: Default::default(),
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            reference.as_raw(),
+        );
+        Self::with_reference(device, format, img_size, &reference_texture, false, filter)
+    }
+
+    // compare against something rendered each frame at the image's size, like another shader, drawn to
+    // `reference_view` before `draw`
+    pub fn rendered(device: &wgpu::Device, format: wgpu::TextureFormat, img_size: (u32, u32), filter: wgpu::FilterMode) -> Self {
+        let reference_texture = create_target(device, "reference_target", format, img_size);
+        Self::with_reference(device, format, img_size, &reference_texture, true, filter)
+    }
+
+    fn with_reference(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        img_size: (u32, u32),
+        reference_texture: &wgpu::Texture,
+        rendered: bool,
+        filter: wgpu::FilterMode,
+    ) -> Self {
+        // the live render is drawn here at the image's own size each frame, then placed like any other image
+        let live_texture = create_target(device, "live_texture", format, img_size);
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Compare Buffer"),
+            contents: bytemuck::bytes_of(&<CompareUniform as bytemuck::Zeroable>::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("compare_bind_group_layout"),
+            entries: &[
+                // binding 0: live render
+                texture_entry(0),
+
+                // binding 1: reference image
+                texture_entry(1),
+
+                // binding 2: sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+
+                // binding 3: uniform buffer (Compare)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let live_view = live_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let reference_view = reference_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = create_bind_group(device, &bind_group_layout, &live_view, &reference_view, &buffer, filter);
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Compare Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/compare.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compare Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // no vertex buffer, the shader makes the quads from the vertex and instance index
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Compare Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Option::from("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Option::from("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            difference: false,
+            img_size,
+            reference_size: (reference_texture.width(), reference_texture.height()),
+            rendered,
+            live_view,
+            reference_view,
+            buffer,
+            bind_group_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    // follow the image's linear or nearest filtering, which needs a new sampler and so a new bind group
+    pub fn set_filter(&mut self, device: &wgpu::Device, filter: wgpu::FilterMode) {
+        self.bind_group = create_bind_group(device, &self.bind_group_layout, &self.live_view, &self.reference_view, &self.buffer, filter);
+    }
+
+    // where the live render should be drawn each frame, with `render::FULL_VIEW`
+    pub fn live_view(&self) -> &wgpu::TextureView {
+        &self.live_view
+    }
+
+    // where the reference should be drawn each frame if it's `rendered`, with `render::FULL_VIEW`
+    pub fn reference_view(&self) -> &wgpu::TextureView {
+        &self.reference_view
+    }
+
+    // draw the comparison over `target`, the live render is placed with `settings` like it normally would be
+    pub fn draw(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        window_size: (u32, u32),
+        settings: &mut ViewSettings,
+        bounds: Option<view::Bounds>,
+    ) {
+        let uniform = if self.difference {
+            // the reference is stretched over the live render so any difference in size is fitted away
+            let live = view::compute_view(self.img_size, window_size, bounds, settings);
+            CompareUniform { live: live.into(), reference: live.into(), mode: 1, _pad: [0; 3] }
+        } else {
+            // each gets half the window, the reference is always letterboxed to fit its half
+            let half = ((window_size.0 / 2).max(1), window_size.1);
+            let mut fit = ViewSettings { auto_fit: true, native: false, integer_scale: false, zoom: 1.0, pan: [0.0, 0.0], bezel: 0 };
+            let live = view::compute_view(self.img_size, half, bounds, settings);
+            let reference = if self.rendered {
+                live
+            } else {
+                view::compute_view(self.reference_size, half, None, &mut fit)
+            };
+            CompareUniform { live: into_half(live, false), reference: into_half(reference, true), mode: 0, _pad: [0; 3] }
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Compare Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..if self.difference { 1 } else { 2 });
+    }
+}
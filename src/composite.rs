@@ -0,0 +1,255 @@
+use wgpu::util::DeviceExt;
+use crate::pool::TexturePool;
+
+// how the scaled render is resampled to the window size
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Downsample {
+    Bilinear,
+    Tent,
+    Lanczos,
+}
+
+impl std::str::FromStr for Downsample {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bilinear" => Ok(Self::Bilinear),
+            "tent" => Ok(Self::Tent),
+            "lanczos" => Ok(Self::Lanczos),
+            _ => Err(format!("unknown filter {}, expected bilinear, tent or lanczos", s)),
+        }
+    }
+}
+
+// uniforms for the composite shader, must match `Composite` in composite.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+struct CompositeUniform {
+    dest_size: [f32; 2],
+    filter_mode: u32,
+    _pad: u32,
+}
+
+// renders the scene at a different resolution to the window then resamples it onto the window
+pub struct Composite {
+    pub render_scale: f32,
+    // the longest the scaled render's long edge can be, however big the window gets
+    pub max_dim: Option<u32>,
+    pub downsample: Downsample,
+    format: wgpu::TextureFormat,
+    sampler: wgpu::Sampler,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+
+    // the scaled render target, replaced whenever the window size changes
+    target: Option<(wgpu::Texture, wgpu::TextureView, wgpu::BindGroup)>,
+    // targets from earlier window sizes, so resizing back and forth doesn't keep allocating
+    pool: TexturePool,
+}
+
+impl Composite {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        render_scale: f32,
+        max_dim: Option<u32>,
+        downsample: Downsample,
+    ) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("composite_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Composite Buffer"),
+            contents: bytemuck::bytes_of(&CompositeUniform { dest_size: [1.0, 1.0], filter_mode: 0, _pad: 0 }),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("composite_bind_group_layout"),
+            entries: &[
+                // binding 0: scaled render
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+
+                // binding 1: sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+
+                // binding 2: uniform buffer (Composite)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/composite.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Composite Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // no vertex buffer, the shader makes a fullscreen triangle from the vertex index
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Composite Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Option::from("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Option::from("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            render_scale,
+            max_dim,
+            downsample,
+            format,
+            sampler,
+            buffer,
+            bind_group_layout,
+            pipeline,
+            target: None,
+            pool: TexturePool::default(),
+        }
+    }
+
+    // whether the scene needs to go through the scaled target at all for a window of `window_size`
+    pub fn enabled(&self, window_size: (u32, u32)) -> bool {
+        self.scaled_size(window_size) != window_size
+    }
+
+    // size of the scaled render for a window of `window_size`, shrunk to fit `max_dim` keeping the aspect
+    pub fn scaled_size(&self, window_size: (u32, u32)) -> (u32, u32) {
+        let long_edge = window_size.0.max(window_size.1) as f32 * self.render_scale;
+        let scale = match self.max_dim {
+            Some(max_dim) if long_edge > max_dim as f32 => self.render_scale * max_dim as f32 / long_edge,
+            _ => self.render_scale,
+        };
+        let scale = |v: u32| ((v as f32 * scale).round() as u32).max(1);
+        (scale(window_size.0), scale(window_size.1))
+    }
+
+    // get the view to render the scene into, making a new target if the window has changed size
+    pub fn target_view(&mut self, device: &wgpu::Device, window_size: (u32, u32)) -> &wgpu::TextureView {
+        let (width, height) = self.scaled_size(window_size);
+        let stale = match &self.target {
+            Some((texture, _, _)) => texture.width() != width || texture.height() != height,
+            None => true,
+        };
+
+        if stale {
+            if let Some((old, _, _)) = self.target.take() {
+                self.pool.give(old);
+            }
+            let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+            let texture = self.pool.take(device, "scaled_target", self.format, (width, height), usage);
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("composite_bind_group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.buffer.as_entire_binding(),
+                    },
+                ],
+            });
+            self.target = Some((texture, view, bind_group));
+        }
+
+        &self.target.as_ref().unwrap().1
+    }
+
+    // resample the scaled render onto `dest`
+    pub fn draw(&self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, dest: &wgpu::TextureView, dest_size: (u32, u32)) {
+        let Some((_, _, bind_group)) = &self.target else {
+            return;
+        };
+
+        let filter_mode = match self.downsample {
+            Downsample::Bilinear => 0,
+            Downsample::Tent => 1,
+            Downsample::Lanczos => 2,
+        };
+        let uniform = CompositeUniform {
+            dest_size: [dest_size.0 as f32, dest_size.1 as f32],
+            filter_mode,
+            _pad: 0,
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: dest,
+                depth_slice: None,
+                resolve_target: None,
+                // any bars around the scene are transparent black, so a --transparent window shows through them
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
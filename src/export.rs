@@ -0,0 +1,614 @@
+use image::{DynamicImage, ImageBuffer, Luma, Rgba, RgbaImage};
+use crate::automation::Automation;
+use crate::render::{Params, Renderer, ScratchTarget};
+use crate::text::{Corner, TextBlock, TextRenderer};
+
+// the format 8 bit frames are rendered in, matching the input texture so colours round trip exactly
+pub const EXPORT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+// 16 bit frames are rendered as linear half floats then encoded to srgb when they're read back
+pub const EXPORT_FORMAT_16: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+// render one frame offscreen at the given size with `draw` and read it back into an image
+pub fn render_to_image(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    size: (u32, u32),
+    format: wgpu::TextureFormat,
+    draw: impl FnOnce(&mut wgpu::CommandEncoder, &wgpu::TextureView),
+) -> DynamicImage {
+    let (width, height) = size;
+    let extent = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("export_target"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: Default::default(),
+    });
+    let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // rows copied out of a texture have to be padded to a multiple of 256 bytes
+    let unpadded_row = format.block_copy_size(None).expect("Export format has no copy size") * width;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row = unpadded_row.div_ceil(align) * align;
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("export_readback"),
+        size: (padded_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Export Encoder"),
+    });
+    draw(&mut encoder, &target_view);
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture: &target,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &readback,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(height),
+            },
+        },
+        extent,
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // wait for the gpu to finish and copy the rows out without their padding
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.expect("Failed to map readback buffer"));
+    device.poll(wgpu::PollType::wait_indefinitely()).expect("Failed to wait for the gpu");
+
+    let data = slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity((unpadded_row * height) as usize);
+    for row in data.chunks(padded_row as usize) {
+        pixels.extend_from_slice(&row[..unpadded_row as usize]);
+    }
+    drop(data);
+    readback.unmap();
+
+    if format == EXPORT_FORMAT_16 {
+        let pixels = pixels
+            .chunks_exact(2)
+            .enumerate()
+            .map(|(i, bytes)| {
+                let value = f16_to_f32(u16::from_le_bytes([bytes[0], bytes[1]]));
+                // every fourth channel is alpha, which stays linear
+                let encoded = if i % 4 == 3 { value } else { linear_to_srgb(value) };
+                (encoded.clamp(0.0, 1.0) * 65535.0).round() as u16
+            })
+            .collect();
+        let img = ImageBuffer::<Rgba<u16>, _>::from_raw(width, height, pixels).expect("Readback was the wrong size");
+        return DynamicImage::ImageRgba16(img);
+    }
+
+    DynamicImage::ImageRgba8(RgbaImage::from_raw(width, height, pixels).expect("Readback was the wrong size"))
+}
+
+// unpack a half float, there's no f16 in stable rust yet
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let fraction = (bits & 0x3ff) as f32 / 1024.0;
+    match exponent {
+        0 => sign * fraction * 2f32.powi(-14),
+        31 => if fraction == 0.0 { sign * f32::INFINITY } else { f32::NAN },
+        _ => sign * (1.0 + fraction) * 2f32.powi(exponent - 15),
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// premultiply (or un-premultiply) alpha so resizing doesn't bleed the colour of transparent pixels into edges
+fn premultiply(img: &mut DynamicImage, undo: bool) {
+    match img {
+        DynamicImage::ImageRgba8(img) => premultiply_pixels(img.pixels_mut().map(|p| &mut p.0), undo, u8::MAX, |v| v as u8),
+        DynamicImage::ImageRgba16(img) => premultiply_pixels(img.pixels_mut().map(|p| &mut p.0), undo, u16::MAX, |v| v as u16),
+        _ => unreachable!("exports are always rgba8 or rgba16"),
+    }
+}
+
+fn premultiply_pixels<'a, T: Copy + Into<f32> + 'a>(
+    pixels: impl Iterator<Item = &'a mut [T; 4]>,
+    undo: bool,
+    max: T,
+    from_f32: impl Fn(f32) -> T,
+) {
+    let max = max.into();
+    for pixel in pixels {
+        let a = pixel[3].into() / max;
+        if undo && a == 0.0 {
+            continue;
+        }
+        for c in &mut pixel[..3] {
+            let v = if undo { (*c).into() / a } else { (*c).into() * a };
+            *c = from_f32(v.round().min(max));
+        }
+    }
+}
+
+// a single channel of the shader's output, for pulling masks out of it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Channel {
+    Alpha,
+    Luma,
+    Red,
+    Green,
+    Blue,
+}
+
+impl std::str::FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alpha" => Ok(Self::Alpha),
+            "luma" => Ok(Self::Luma),
+            "r" => Ok(Self::Red),
+            "g" => Ok(Self::Green),
+            "b" => Ok(Self::Blue),
+            _ => Err(format!("unknown channel {}, expected alpha, luma, r, g or b", s)),
+        }
+    }
+}
+
+// pull one channel out of an export as a grayscale image with the same bits per channel. luma uses the
+// rec. 709 weights on the encoded values
+pub fn extract_channel(img: &DynamicImage, channel: Channel) -> DynamicImage {
+    let pick = |p: [f32; 4]| match channel {
+        Channel::Alpha => p[3],
+        Channel::Luma => 0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2],
+        Channel::Red => p[0],
+        Channel::Green => p[1],
+        Channel::Blue => p[2],
+    };
+    match img {
+        DynamicImage::ImageRgba16(img) => DynamicImage::ImageLuma16(ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+            Luma([pick(img.get_pixel(x, y).0.map(f32::from)).round() as u16])
+        })),
+        img => {
+            let img = img.to_rgba8();
+            DynamicImage::ImageLuma8(ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+                Luma([pick(img.get_pixel(x, y).0.map(f32::from)).round() as u8])
+            }))
+        }
+    }
+}
+
+// how an image is fitted into a fixed export aspect
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExportFit {
+    // pad the short side with transparency so the whole image is kept
+    Letterbox,
+    // trim the long side, keeping the middle of the image
+    Crop,
+}
+
+impl std::str::FromStr for ExportFit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "letterbox" => Ok(Self::Letterbox),
+            "crop" => Ok(Self::Crop),
+            _ => Err(format!("unknown fit {}, expected letterbox or crop", s)),
+        }
+    }
+}
+
+// a fixed width:height for exported frames, so a batch comes out the same shape whatever the image
+#[derive(Copy, Clone, Debug)]
+pub struct ExportAspect {
+    pub width: u32,
+    pub height: u32,
+    pub fit: ExportFit,
+}
+
+// parse an aspect like 16:9, both sides must be above 0
+pub fn parse_aspect(s: &str) -> Option<(u32, u32)> {
+    let (w, h) = s.split_once(':')?;
+    let (w, h) = (w.trim().parse().ok()?, h.trim().parse().ok()?);
+    (w > 0 && h > 0).then_some((w, h))
+}
+
+impl ExportAspect {
+    // size of the frame for an image of `size`, growing one side to letterbox or shrinking one to crop
+    pub fn frame_size(&self, size: (u32, u32)) -> (u32, u32) {
+        let (w, h) = (size.0 as u64, size.1 as u64);
+        let (aw, ah) = (self.width as u64, self.height as u64);
+        let wider = w * ah > h * aw;
+        let keep_width = match self.fit {
+            ExportFit::Letterbox => wider,
+            ExportFit::Crop => !wider,
+        };
+        if keep_width {
+            (size.0, ((w * ah + aw / 2) / aw).max(1) as u32)
+        } else {
+            (((h * aw + ah / 2) / ah).max(1) as u32, size.1)
+        }
+    }
+
+    // centre `img` in a frame of this aspect, anything outside the image is left transparent
+    pub fn apply(&self, img: &DynamicImage) -> DynamicImage {
+        let (width, height) = self.frame_size((img.width(), img.height()));
+        let mut frame = DynamicImage::new(width, height, img.color());
+        let x = (width as i64 - img.width() as i64) / 2;
+        let y = (height as i64 - img.height() as i64) / 2;
+        image::imageops::replace(&mut frame, img, x, y);
+        frame
+    }
+}
+
+// offscreen rendering with the settings every export shares
+pub struct Exporter<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub renderer: &'a mut Renderer,
+
+    // render this many times bigger then scale down, for cleaner scanlines and edges
+    pub oversample: u32,
+
+    // draws the hud and caption, only needed if one of them is on
+    pub text: Option<&'a mut TextRenderer>,
+
+    // burn the effect, time and params into each frame
+    pub burn_hud: bool,
+
+    // the effect's name for the hud
+    pub effect: &'a str,
+
+    // a line of text along the bottom of each frame
+    pub caption: Option<String>,
+
+    // export the shader's debug output (from `fs_debug`) instead of the final colour
+    pub debug_channel: bool,
+
+    // bits per channel of the exported images, 8 or 16
+    pub bit_depth: u32,
+
+    // letterbox or crop every frame to this aspect instead of keeping the image's own
+    pub aspect: Option<ExportAspect>,
+
+    // params that change over the loop, applied to each frame of a sprite sheet
+    pub automation: Option<&'a Automation>,
+}
+
+// what went into an exported image, enough to make it again
+pub struct ExportInfo {
+    pub effect: String,
+    // the params each frame was drawn with, time and automation included, in the order they appear
+    pub frames: Vec<Params>,
+    pub oversample: u32,
+    pub bit_depth: u32,
+}
+
+// one frame's params as a json object, with everything that changes what's drawn
+fn params_json(p: &Params) -> String {
+    let fields = [
+        ("time", p.time.to_string()),
+        ("frame", p.frame.to_string()),
+        ("artifact_amplifier", p.artifact_amplifier.to_string()),
+        ("crt_amount_adjusted", p.crt_amount_adjusted.to_string()),
+        ("bloom_fac", p.bloom_fac.to_string()),
+        ("crt_curvature", p.crt_curvature.to_string()),
+        ("chromatic_aberration", p.chromatic_aberration.to_string()),
+        ("component_mask", p.component_mask.to_string()),
+        ("mask_strength", p.mask_strength.to_string()),
+        ("corner_radius", p.corner_radius.to_string()),
+        ("scanline_offset", p.scanline_offset.to_string()),
+        ("scanline_overlay", p.scanline_overlay.to_string()),
+        ("dither", p.dither.to_string()),
+        ("global_opacity", p.global_opacity.to_string()),
+        ("uv_test", p.uv_test.to_string()),
+    ];
+    let fields: Vec<String> = fields.iter().map(|(name, value)| format!("      \"{}\": {}", name, value)).collect();
+    format!("    {{\n{}\n    }}", fields.join(",\n"))
+}
+
+impl ExportInfo {
+    pub fn to_json(&self) -> String {
+        let frames: Vec<String> = self.frames.iter().map(params_json).collect();
+        format!(
+            concat!(
+                "{{\n",
+                "  \"effect\": \"{}\",\n",
+                "  \"frames\": [\n{}\n  ],\n",
+                "  \"oversample\": {},\n",
+                "  \"bit_depth\": {}\n",
+                "}}\n",
+            ),
+            self.effect.replace('\\', "\\\\").replace('"', "\\\""),
+            frames.join(",\n"),
+            self.oversample,
+            self.bit_depth,
+        )
+    }
+
+    // write the json next to `image_path`, with the same name but a .json extension
+    pub fn write_sidecar(&self, image_path: &str) -> std::io::Result<std::path::PathBuf> {
+        let path = std::path::Path::new(image_path).with_extension("json");
+        std::fs::write(&path, self.to_json())?;
+        Ok(path)
+    }
+}
+
+// write each image as a page of one tiff, named after its layer. editors that open multi-page tiffs as
+// layers (gimp, krita, photoshop) get the original and the shaded render stacked ready to composite
+pub fn write_layers(path: &str, layers: &[(&str, &DynamicImage)]) -> Result<(), String> {
+    use tiff::encoder::{colortype, TiffEncoder};
+    use tiff::tags::Tag;
+
+    // tiff's PageName tag, which is what names the layers
+    const PAGE_NAME: Tag = Tag::Unknown(285);
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut encoder = TiffEncoder::new(std::io::BufWriter::new(file)).map_err(|e| e.to_string())?;
+    for &(name, img) in layers {
+        let (width, height) = (img.width(), img.height());
+        let result = match img {
+            DynamicImage::ImageRgba16(img) => encoder.new_image::<colortype::RGBA16>(width, height).and_then(|mut page| {
+                page.encoder().write_tag(PAGE_NAME, name)?;
+                page.write_data(img.as_raw())
+            }),
+            img => encoder.new_image::<colortype::RGBA8>(width, height).and_then(|mut page| {
+                page.encoder().write_tag(PAGE_NAME, name)?;
+                page.write_data(img.to_rgba8().as_raw())
+            }),
+        };
+        result.map_err(|e| format!("Failed to write layer {}: {}", name, e))?;
+    }
+    Ok(())
+}
+
+// a 64 bit fnv-1a hash of a frame's size and pixels. written out here rather than using the std hasher, which
+// isn't promised to give the same answer across rust versions, so hashes from different builds can be compared
+pub fn frame_hash(img: &DynamicImage) -> u64 {
+    let size = [img.width().to_le_bytes(), img.height().to_le_bytes()].concat();
+    size.iter().chain(img.as_bytes()).fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+// the info overlay, kept to short lines so it fits across a single card
+pub fn hud_lines(effect: &str, params: &Params) -> Vec<String> {
+    vec![
+        effect.to_string(),
+        format!("t {:.2}s", params.time),
+        format!("amp {:.2}", params.artifact_amplifier),
+        format!("crt {:.2}", params.crt_amount_adjusted),
+        format!("bloom {:.2}", params.bloom_fac),
+        format!("curve {:.2}", params.crt_curvature),
+        format!("ca {:.2}", params.chromatic_aberration),
+    ]
+}
+
+impl Exporter<'_> {
+    fn format(&self) -> wgpu::TextureFormat {
+        if self.bit_depth == 16 { EXPORT_FORMAT_16 } else { EXPORT_FORMAT }
+    }
+
+    // render one frame at `size` onto a transparent background, so exports keep the image's alpha
+    fn render_at(&mut self, params: &Params, size: (u32, u32)) -> DynamicImage {
+        let format = self.format();
+        let renderer = &mut *self.renderer;
+        let text = self.text.as_deref_mut();
+        let burn_hud = self.burn_hud;
+        let effect = self.effect;
+        let caption = self.caption.clone().map(|caption| vec![caption]);
+        let device = self.device;
+        let scale = self.oversample as f32;
+
+        // for the debug channel the final colour goes somewhere we throw away and the debug output gets read back
+        let mut scratch = self.debug_channel.then(|| ScratchTarget::new(format));
+        let scratch = scratch.as_mut().map(|scratch| scratch.view(device, size));
+
+        render_to_image(self.device, self.queue, size, format, |encoder, target| {
+            let clear = wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT);
+            match scratch {
+                Some(scratch) => renderer.draw(device, encoder, scratch, format, clear, Some(target)),
+                None => renderer.draw(device, encoder, target, format, clear, None),
+            }
+            let Some(text) = text else {
+                return;
+            };
+            if burn_hud {
+                let lines = hud_lines(effect, params);
+                let block = TextBlock { lines: &lines, corner: Corner::TopLeft, scale, color: [1.0, 1.0, 1.0, 1.0] };
+                text.draw(device, encoder, target, format, size, &block);
+            }
+            if let Some(lines) = &caption {
+                let block = TextBlock { lines, corner: Corner::BottomLeft, scale, color: [1.0, 1.0, 1.0, 1.0] };
+                text.draw(device, encoder, target, format, size, &block);
+            }
+        })
+    }
+
+    // size of each exported frame, the image's size unless it's being fitted to an aspect
+    pub fn frame_size(&self) -> (u32, u32) {
+        let size = self.renderer.image_size();
+        self.aspect.map_or(size, |aspect| aspect.frame_size(size))
+    }
+
+    // render one frame with `params`, at the image's size then fitted to the export aspect if there is one
+    pub fn render_frame(&mut self, params: &Params) -> DynamicImage {
+        let img = self.render_image(params);
+        match self.aspect {
+            Some(aspect) => aspect.apply(&img),
+            None => img,
+        }
+    }
+
+    fn render_image(&mut self, params: &Params) -> DynamicImage {
+        self.renderer.write_params(self.queue, params);
+        let (width, height) = self.renderer.image_size();
+        if self.oversample <= 1 {
+            return self.render_at(params, (width, height));
+        }
+
+        let big_size = (width * self.oversample, height * self.oversample);
+        let mut big = self.render_at(params, big_size);
+        premultiply(&mut big, false);
+        let mut img = big.resize_exact(width, height, image::imageops::FilterType::Lanczos3);
+        premultiply(&mut img, true);
+        img
+    }
+
+    // an empty sheet big enough for `frames` frames in a grid `cols` wide
+    fn blank_sheet(&self, frames: u32, cols: u32) -> DynamicImage {
+        let (frame_w, frame_h) = self.frame_size();
+        let rows = frames.div_ceil(cols);
+        if self.bit_depth == 16 {
+            DynamicImage::new_rgba16(frame_w * cols, frame_h * rows)
+        } else {
+            DynamicImage::new_rgba8(frame_w * cols, frame_h * rows)
+        }
+    }
+
+    // render `frames` evenly spaced frames across the loop, each with the params it was drawn with
+    pub fn render_loop(&mut self, params: &Params, loop_time: f32, frames: u32) -> Vec<(DynamicImage, Params)> {
+        (0..frames)
+            .map(|i| {
+                let mut params = *params;
+                params.time = loop_time * i as f32 / frames as f32;
+                params.frame = i;
+                if let Some(automation) = self.automation {
+                    automation.apply(params.time, &mut params);
+                }
+                (self.render_frame(&params), params)
+            })
+            .collect()
+    }
+
+    // render `frames` evenly spaced frames across the loop and pack them into a grid `cols` wide, along with
+    // the params of each frame
+    pub fn render_spritesheet(&mut self, params: &Params, loop_time: f32, frames: u32, cols: u32) -> (DynamicImage, Vec<Params>) {
+        let (frame_w, frame_h) = self.frame_size();
+        let mut sheet = self.blank_sheet(frames, cols);
+        let mut used = Vec::new();
+
+        for (i, (frame, params)) in (0..).zip(self.render_loop(params, loop_time, frames)) {
+            let (x, y) = ((i % cols) * frame_w, (i / cols) * frame_h);
+            image::imageops::replace(&mut sheet, &frame, x as i64, y as i64);
+            used.push(params);
+        }
+
+        (sheet, used)
+    }
+
+    // render a frame for each of `values` of the param `name` (from `SWEEPABLE`), each labelled with its
+    // value, and pack them into a grid `cols` wide
+    pub fn render_sweep(&mut self, params: &Params, (name, label): (&str, &str), values: &[f32], cols: u32) -> DynamicImage {
+        let (frame_w, frame_h) = self.frame_size();
+        let mut sheet = self.blank_sheet(values.len() as u32, cols);
+
+        for (i, &value) in values.iter().enumerate() {
+            let mut params = *params;
+            *params.value_mut(name).expect("Sweep param isn't in SWEEPABLE") = value;
+            self.caption = Some(format!("{} {:.2}", label, value));
+            let frame = self.render_frame(&params);
+
+            let i = i as u32;
+            let (x, y) = ((i % cols) * frame_w, (i / cols) * frame_h);
+            image::imageops::replace(&mut sheet, &frame, x as i64, y as i64);
+        }
+        self.caption = None;
+
+        sheet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_aspects() {
+        assert_eq!(parse_aspect("16:9"), Some((16, 9)));
+        assert_eq!(parse_aspect(" 4 : 3 "), Some((4, 3)));
+        for bad in ["", "16", "16:", ":9", "0:1", "1:0", "16x9", "-16:9", "1.5:1"] {
+            assert_eq!(parse_aspect(bad), None, "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn letterbox_grows_a_side() {
+        let aspect = ExportAspect { width: 16, height: 9, fit: ExportFit::Letterbox };
+        assert_eq!(aspect.frame_size((71, 95)), (169, 95));
+        assert_eq!(aspect.frame_size((1600, 90)), (1600, 900));
+        assert_eq!(aspect.frame_size((160, 90)), (160, 90));
+    }
+
+    #[test]
+    fn crop_shrinks_a_side() {
+        let aspect = ExportAspect { width: 16, height: 9, fit: ExportFit::Crop };
+        assert_eq!(aspect.frame_size((71, 95)), (71, 40));
+        assert_eq!(aspect.frame_size((1600, 90)), (160, 90));
+
+        // never rounds down to nothing
+        let aspect = ExportAspect { width: 1000, height: 1, fit: ExportFit::Crop };
+        assert_eq!(aspect.frame_size((1, 1)), (1, 1));
+    }
+
+    #[test]
+    fn frame_hash_is_stable() {
+        // pinned, so a hash printed by one build can be checked against another
+        assert_eq!(frame_hash(&DynamicImage::new_rgba8(0, 0)), 0xa8c7_f832_281a_39c5);
+        let img = image::RgbaImage::from_raw(2, 1, vec![1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        assert_eq!(frame_hash(&DynamicImage::ImageRgba8(img)), 0xf86d_d2cd_636c_cf86);
+    }
+
+    #[test]
+    fn frame_hash_sees_size_and_pixels() {
+        let img = image::RgbaImage::from_pixel(2, 2, image::Rgba([10, 20, 30, 255]));
+        let mut changed = img.clone();
+        changed.put_pixel(1, 1, image::Rgba([10, 20, 31, 255]));
+        // same bytes laid out differently
+        let reshaped = image::RgbaImage::from_raw(4, 1, img.as_raw().clone()).unwrap();
+
+        let hash = frame_hash(&DynamicImage::ImageRgba8(img));
+        assert_ne!(hash, frame_hash(&DynamicImage::ImageRgba8(changed)));
+        assert_ne!(hash, frame_hash(&DynamicImage::ImageRgba8(reshaped)));
+    }
+
+    #[test]
+    fn hud_leads_with_the_effect() {
+        let params = Params { time: 1.5, ..Params::default() };
+        let lines = hud_lines("foil", &params);
+        assert_eq!(lines[..2], ["foil", "t 1.50s"]);
+    }
+
+    #[test]
+    fn export_info_has_every_param() {
+        let info = ExportInfo { effect: String::from("foil"), frames: vec![Params::default()], oversample: 1, bit_depth: 8 };
+        let json = info.to_json();
+        let names = crate::render::SWEEPABLE.iter().map(|&(name, _)| name);
+        for name in names.chain(["frame", "component_mask", "scanline_overlay", "dither", "uv_test"]) {
+            assert!(json.contains(&format!("\"{}\": ", name)), "{} missing from {}", name, json);
+        }
+    }
+
+    #[test]
+    fn export_info_keeps_each_frame() {
+        let first = Params { time: 0.5, frame: 1, bloom_fac: 0.25, ..Params::default() };
+        let second = Params { time: 1.5, frame: 2, bloom_fac: 1.75, ..Params::default() };
+        let json = ExportInfo { effect: String::from("foil"), frames: vec![first, second], oversample: 1, bit_depth: 8 }.to_json();
+        let (a, b) = json.split_once("},\n    {").expect("two frame objects");
+        assert!(a.contains("\"time\": 0.5") && a.contains("\"frame\": 1,") && a.contains("\"bloom_fac\": 0.25"), "{}", a);
+        assert!(b.contains("\"time\": 1.5") && b.contains("\"frame\": 2,") && b.contains("\"bloom_fac\": 1.75"), "{}", b);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::view::View;
+
+// how far the glow reaches past the card, in image pixels
+pub const GLOW_EXTENT: u32 = 12;
+
+// corner radius used with --card-glow when none is given, about what the game's cards have at 1x
+pub const DEFAULT_CORNER_RADIUS: f32 = 5.0;
+
+// the colours of the card rarities in the game, usable by name instead of a hex colour
+const RARITIES: [(&str, &str); 4] = [
+    ("common", "009dff"),
+    ("uncommon", "4bc292"),
+    ("rare", "fe5f55"),
+    ("legendary", "b26cbb"),
+];
+
+// turn a rarity name or an `rrggbb` hex colour into linear rgba
+pub fn parse_glow_color(value: &str) -> Option<[f32; 4]> {
+    let hex = RARITIES.iter().find(|(name, _)| name.eq_ignore_ascii_case(value)).map_or(value, |&(_, hex)| hex);
+    crate::bezel::parse_color(hex)
+}
+
+// the shape the glow goes around, in image pixels
+pub struct Silhouette {
+    pub size: [f32; 2],
+    pub radius: f32,
+}
+
+// uniforms for the glow shader, must match `Glow` in glow.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+struct GlowUniform {
+    quad_scale: [f32; 2],
+    quad_offset: [f32; 2],
+    tilt: [f32; 2],
+    size: [f32; 2],
+    color: [f32; 4],
+    extent: f32,
+    radius: f32,
+    strength: f32,
+    _pad: f32,
+}
+
+// soft halo in a rarity colour around the card's rounded silhouette, like the highlight on a selected card
+pub struct Glow {
+    pub color: [f32; 4],
+    pub strength: f32,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Glow {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, color: [f32; 4], strength: f32) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Glow Buffer"),
+            size: std::mem::size_of::<GlowUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("glow_bind_group_layout"),
+            entries: &[
+                // binding 0: uniform buffer (Glow)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("glow_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Glow Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/glow.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Glow Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // no vertex buffer, the shader makes the quad from the vertex index
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Glow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Option::from("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Option::from("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self { color, strength, buffer, bind_group, pipeline }
+    }
+
+    // draw the glow around `silhouette`, `view` is the silhouette grown by `GLOW_EXTENT` (see `view::grow_view`).
+    // the card should be drawn over it afterwards
+    pub fn draw(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        view: &View,
+        silhouette: &Silhouette,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        let uniform = GlowUniform {
+            quad_scale: view.quad_scale,
+            quad_offset: view.quad_offset,
+            tilt: view.tilt,
+            size: silhouette.size,
+            color: self.color,
+            extent: GLOW_EXTENT as f32,
+            radius: silhouette.radius,
+            strength: self.strength,
+            _pad: 0.0,
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Glow Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
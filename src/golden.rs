@@ -0,0 +1,64 @@
+use image::{Rgba, RgbaImage};
+use crate::render::Params;
+
+// everything about the golden frame is fixed so it comes out the same on every machine
+pub const GOLDEN_TIME: f32 = 1.0;
+pub const GOLDEN_OUT: &str = "golden.png";
+
+// the same size as a card in the game's 1x atlas
+const CARD_SIZE: (u32, u32) = (71, 95);
+const CORNER_RADIUS: i32 = 5;
+
+pub fn golden_params() -> Params {
+    Params { time: GOLDEN_TIME, ..Params::default() }
+}
+
+// a simple card drawn in code, so there's no asset to go missing and no decoder differences between machines.
+// integer maths only so it's the same everywhere
+pub fn sample_card() -> RgbaImage {
+    let (width, height) = CARD_SIZE;
+    let (w, h) = (width as i32, height as i32);
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (x, y) = (x as i32, y as i32);
+
+        // transparent outside the rounded corners
+        let cx = x.clamp(CORNER_RADIUS, w - 1 - CORNER_RADIUS);
+        let cy = y.clamp(CORNER_RADIUS, h - 1 - CORNER_RADIUS);
+        let (dx, dy) = (x - cx, y - cy);
+        if dx * dx + dy * dy > CORNER_RADIUS * CORNER_RADIUS {
+            return Rgba([0, 0, 0, 0]);
+        }
+
+        // light border
+        let edge = x.min(y).min(w - 1 - x).min(h - 1 - y);
+        if edge < 2 {
+            return Rgba([235, 232, 224, 255]);
+        }
+
+        // red diamond in the middle
+        let (mx, my) = (x - w / 2, y - h / 2);
+        if mx.abs() * 3 + my.abs() * 2 < 48 {
+            let shade = 200 - (my + 24).clamp(0, 48) as u8;
+            return Rgba([shade.saturating_add(30), 40, 50, 255]);
+        }
+
+        // dark blue background with diagonal stripes, plenty of detail for the scanlines and flicker to show on
+        let stripe = if (x + y) % 8 < 4 { 12 } else { 0 };
+        let gradient = (y * 40 / h) as u8;
+        Rgba([20 + stripe, 30 + stripe + gradient, 70 + stripe + gradient, 255])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_card_is_pinned() {
+        // if this changes, so does every golden frame made from it
+        let card = image::DynamicImage::ImageRgba8(sample_card());
+        assert_eq!((card.width(), card.height()), CARD_SIZE);
+        assert_eq!(crate::export::frame_hash(&card), 0x74c4_6bef_2ad4_2d6e);
+    }
+}
@@ -0,0 +1,143 @@
+use winit::event::WindowEvent;
+use winit::window::Window;
+use crate::playback::Playback;
+use crate::render::Params;
+
+// egui overlay drawn on top of the shaded image
+pub struct Gui {
+    ctx: egui::Context,
+    state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+}
+
+impl Gui {
+    pub fn new(window: &Window, device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let ctx = egui::Context::default();
+        let state = egui_winit::State::new(
+            ctx.clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            None,
+            Some(device.limits().max_texture_dimension_2d as usize),
+        );
+        let renderer = egui_wgpu::Renderer::new(device, surface_format, Default::default());
+
+        Self { ctx, state, renderer }
+    }
+
+    // pass a window event to egui, returns true if egui used it and we should ignore it
+    pub fn on_window_event(&mut self, window: &Window, event: &WindowEvent) -> bool {
+        self.state.on_window_event(window, event).consumed
+    }
+
+    // build the ui for this frame with `build_ui` and draw it over the top of `view`
+    pub fn draw(
+        &mut self,
+        window: &Window,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        build_ui: impl FnMut(&egui::Context),
+    ) {
+        let raw_input = self.state.take_egui_input(window);
+        let output = self.ctx.run(raw_input, build_ui);
+        self.state.handle_platform_output(window, output.platform_output);
+
+        let size = window.inner_size();
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [size.width, size.height],
+            pixels_per_point: output.pixels_per_point,
+        };
+        let paint_jobs = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer.update_texture(device, queue, *id, delta);
+        }
+        let commands = self.renderer.update_buffers(device, queue, encoder, &paint_jobs, &screen);
+        queue.submit(commands);
+
+        // draw over the existing frame rather than clearing it
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("GUI Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            }).forget_lifetime();
+            self.renderer.render(&mut render_pass, &paint_jobs, &screen);
+        }
+
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
+
+// play/pause and a seekable progress bar along the bottom of the window
+pub fn playback_panel(ctx: &egui::Context, playback: &mut Playback) {
+    egui::TopBottomPanel::bottom("playback").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            let label = if playback.playing { "⏸" } else { "▶" };
+            if ui.button(label).clicked() {
+                playback.toggle();
+            }
+
+            ui.label(format!("{:.2} / {:.2} s", playback.time, playback.loop_time));
+
+            // progress bar that can be clicked or dragged along to seek
+            let bar = egui::ProgressBar::new(playback.progress()).desired_height(12.0);
+            let response = ui.add(bar).interact(egui::Sense::click_and_drag());
+            if let Some(pos) = response.interact_pointer_pos() {
+                let rect = response.rect;
+                playback.seek((pos.x - rect.left()) / rect.width());
+            }
+        });
+    });
+}
+
+// sliders for each shader parameter, with `effect` (the shader in use) shown above them
+pub fn params_window(ctx: &egui::Context, params: &mut Params, effect: &str) {
+    egui::Window::new("Params").default_open(false).show(ctx, |ui| {
+        ui.heading(effect);
+
+        ui.add(egui::Slider::new(&mut params.artifact_amplifier, 0.0..=3.0).text("artifact amplifier"))
+            .on_hover_text("Shifts the colour bias the game subtracts from every pixel, higher values brighten the image slightly. 0 to 3, the game uses 1.");
+        ui.add(egui::Slider::new(&mut params.crt_amount_adjusted, 0.0..=2.0).text("CRT amount"))
+            .on_hover_text("Overall strength of the CRT look, scales how much the bloom factor affects contrast and brightness. 0 to 2, the game uses 1.");
+        ui.add(egui::Slider::new(&mut params.bloom_fac, 0.0..=2.0).text("bloom factor"))
+            .on_hover_text("How much glow the CRT adds, higher values lift the darks and soften contrast. 0 to 2, the game uses 1.");
+        ui.add(egui::Slider::new(&mut params.crt_curvature, 0.0..=1.0).text("CRT curvature"))
+            .on_hover_text("Bends the image outwards like the glass of an old monitor. 0 (flat, like the game) to 1.");
+        ui.add(egui::Slider::new(&mut params.chromatic_aberration, 0.0..=4.0).text("chromatic aberration"))
+            .on_hover_text("Pulls the red and blue channels apart sideways, in image pixels. 0 (off, like the game) to 4.");
+        ui.add(egui::Slider::new(&mut params.mask_strength, 0.0..=1.0).text("mask strength"))
+            .on_hover_text("How much the --mask image limits where the effect shows. 0 ignores the mask, 1 (the default) follows it exactly.");
+        ui.add(egui::Slider::new(&mut params.corner_radius, 0.0..=16.0).text("corner radius"))
+            .on_hover_text("Rounds off the corners of the card, in image pixels. 0 (square) to 16, the game's cards are about 5.");
+        ui.add(egui::Slider::new(&mut params.scanline_offset, 0.0..=1.0).text("scanline offset"))
+            .on_hover_text("Shifts the scanlines along by a fraction of their spacing, to line them up with the game's. Press L to see where they fall. 0 (like the game) to 1.");
+        ui.add(egui::Slider::new(&mut params.global_opacity, 0.0..=1.0).text("opacity"))
+            .on_hover_text("Fades the whole card, for laying it over a stream. 1 (solid, like the game) to 0 (invisible). - and = step it.");
+
+        // the single knob look from before curvature was its own setting. only the original crt knobs are
+        // touched, the combined term had no curvature in it so that goes back to flat
+        if ui.button("Reset to classic").clicked() {
+            let classic = Params::default();
+            params.artifact_amplifier = classic.artifact_amplifier;
+            params.crt_amount_adjusted = classic.crt_amount_adjusted;
+            params.bloom_fac = classic.bloom_fac;
+            params.crt_curvature = 0.0;
+        }
+    });
+}
@@ -0,0 +1,63 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+// how many recent measurements the average and worst case cover
+const SAMPLES: usize = 60;
+
+// estimates the time from an input event to it reaching the screen. we can only see when a frame is
+// handed to the compositor, so one refresh interval is added on top for scanout
+pub struct LatencyMeter {
+    // earliest input that hasn't been drawn yet
+    pending: Option<Instant>,
+    // input that the frame currently being drawn responds to
+    in_flight: Option<Instant>,
+    refresh_interval: f32,
+    recent: VecDeque<f32>,
+}
+
+impl LatencyMeter {
+    pub fn new(refresh_rate_hz: f32) -> Self {
+        Self {
+            pending: None,
+            in_flight: None,
+            refresh_interval: 1.0 / refresh_rate_hz,
+            recent: VecDeque::with_capacity(SAMPLES),
+        }
+    }
+
+    // an input arrived, only the first one before each frame counts since it's waited longest
+    pub fn input(&mut self) {
+        self.pending.get_or_insert_with(Instant::now);
+    }
+
+    // a frame has started, it'll include everything that's arrived so far
+    pub fn frame_started(&mut self) {
+        self.in_flight = self.pending.take();
+    }
+
+    // the frame has been presented
+    pub fn presented(&mut self) {
+        let Some(input_time) = self.in_flight.take() else {
+            return;
+        };
+        if self.recent.len() == SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(input_time.elapsed().as_secs_f32() + self.refresh_interval);
+    }
+
+    // lines for the overlay, in milliseconds
+    pub fn lines(&self, max_frame_latency: u32) -> Vec<String> {
+        let Some(last) = self.recent.back() else {
+            return vec![String::from("move the mouse")];
+        };
+        let average = self.recent.iter().sum::<f32>() / self.recent.len() as f32;
+        let worst = self.recent.iter().copied().fold(0.0, f32::max);
+        vec![
+            format!("input {:.1} ms", last * 1000.0),
+            format!("avg {:.1} ms", average * 1000.0),
+            format!("max {:.1} ms", worst * 1000.0),
+            format!("queue {}", max_frame_latency),
+        ]
+    }
+}
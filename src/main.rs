@@ -1,53 +1,557 @@
 use std::default::Default;
 use std::sync::Arc;
-use image::{DynamicImage, GenericImageView, RgbaImage};
+use image::{ImageDecoder, RgbaImage};
 use std::env;
 use std::path::Path;
 use std::sync::mpsc::channel;
-use std::time::Duration;
+use std::time::Instant;
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
-use wgpu::Texture;
-use wgpu::util::DeviceExt;
-use winit::event::{Event, WindowEvent};
+use winit::event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
 use winit::window::Window;
 
-// write a new texture to the queue
-fn write_texture(queue: &wgpu::Queue, texture: &Texture, img_path: &String, height: u32, width: u32) {
-    match load_image(&img_path) {
-        Ok(img) => {
-            queue.write_texture(
-                wgpu::TexelCopyTextureInfo {
-                    texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                &img.into_raw(),
-                wgpu::TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(4 * width),
-                    rows_per_image: Some(height),
-                },
-                wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-            );
-        },
-        Err(e) => eprintln!("Failed to load image: {}", e),
-    }
-}
-
-// Parse command line arguments to return an image path
-fn parse_args() -> String {
-
-    // Parse command line arguments
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Image path required.");
+mod bezel;
+mod anim;
+mod automation;
+mod blur;
+mod bundle;
+#[cfg(feature = "osc-clock")]
+mod clock;
+mod compare;
+mod composite;
+mod export;
+mod glow;
+#[cfg(feature = "gui")]
+mod gui;
+mod golden;
+mod latency;
+mod mockup;
+mod playback;
+mod pool;
+mod preset;
+mod render;
+mod shader_check;
+mod snapshot;
+mod text;
+mod tile;
+mod view;
+
+use composite::{Composite, Downsample};
+use mockup::Mockup;
+use playback::Playback;
+use render::{Dither, Params, Renderer};
+use view::{Bounds, ViewSettings};
+
+// the shader every image is drawn with
+const SHADER_SOURCE: &str = include_str!("../shaders/shaders.wgsl");
+
+// what the built-in shader is called in the gui and exported metadata
+const BUILTIN_EFFECT: &str = "Balatro CRT (built-in)";
+
+// dark slate, like the frames in the game's collection view
+const DEFAULT_BEZEL_COLOR: &str = "2b3a3f";
+
+// options that can be passed on the command line
+struct Args {
+    img_path: Option<String>,
+    // any more images after the first, to switch between with Tab
+    extra_images: Vec<String>,
+    auto_fit: bool,
+    native: bool,
+    integer_scale: bool,
+    nearest: bool,
+    loop_time: Option<f32>,
+    speed: Option<f32>,
+    raw_delta: bool,
+    title: String,
+    mockup: bool,
+    // let the desktop show through wherever nothing is drawn, and through the card with --opacity
+    transparent: bool,
+    tile_preview: bool,
+    render_scale: f32,
+    max_render_dim: Option<u32>,
+    downsample: Downsample,
+    backends: wgpu::Backends,
+    // adapter index or part of its name, for machines with more than one gpu
+    gpu: Option<String>,
+    oversample: u32,
+    crt_curvature: f32,
+    chromatic_aberration: f32,
+    mask: Option<String>,
+    mask_strength: f32,
+    corner_radius: Option<f32>,
+    scanline_offset: f32,
+    opacity: f32,
+    uv_test: bool,
+    dither: Dither,
+    // take the time from udp packets sent to this port instead of the internal clock
+    #[cfg(feature = "osc-clock")]
+    clock_port: Option<u16>,
+    // write a wgpu trace of every gpu command here, for attaching to bug reports
+    #[cfg(feature = "gpu-trace")]
+    profile_dir: Option<String>,
+    // keyframed params to play back, as recorded with K
+    automation: Option<String>,
+    // save only this channel of exports, as grayscale
+    channel: Option<export::Channel>,
+    // params loaded with --params, the options above are applied over the top
+    base_params: Params,
+    // where the view starts, the same as scrolling and dragging to it
+    zoom: f32,
+    pan: [f32; 2],
+    card_glow: Option<[f32; 4]>,
+    glow_strength: f32,
+    spritesheet: bool,
+    // save the loop as an animated webp or gif instead of opening a window
+    record: bool,
+    // print a hash of each frame's pixels instead of saving them, for checking renders are deterministic
+    dump_frame_hashes: bool,
+    frames: u32,
+    cols: Option<u32>,
+    out: Option<String>,
+    out_layers: Option<String>,
+    burn_hud: bool,
+    fps_overlay: bool,
+    shader: Option<String>,
+    mrt_debug: bool,
+    pre_blur: f32,
+    pre_blur_iterations: u32,
+    bit_depth: u32,
+    // fixed width:height for exports and whether to letterbox or crop to it
+    export_aspect: Option<(u32, u32)>,
+    export_fit: export::ExportFit,
+    bundle: Option<String>,
+    golden: bool,
+    // render a grid stepping one param from `sweep_from` to `sweep_to`, by its index in render::SWEEPABLE
+    sweep: Option<usize>,
+    sweep_from: Option<f32>,
+    sweep_to: Option<f32>,
+    sweep_steps: u32,
+    bezel: u32,
+    bezel_color: Option<[f32; 4]>,
+    bezel_texture: Option<String>,
+    latency_debug: bool,
+    export_params_json: bool,
+    ignore_icc: bool,
+    reference: Option<String>,
+}
+
+impl Args {
+    // shader params with any overrides from the command line applied
+    fn initial_params(&self) -> Params {
+        Params {
+            crt_curvature: self.crt_curvature,
+            chromatic_aberration: self.chromatic_aberration,
+            mask_strength: self.mask_strength,
+            scanline_offset: self.scanline_offset,
+            global_opacity: self.opacity,
+            uv_test: self.uv_test as u32,
+            dither: self.dither as u32,
+            // the glow is shaped around rounded corners, so it rounds the card's off too unless told otherwise
+            corner_radius: self.corner_radius.unwrap_or(if self.card_glow.is_some() { glow::DEFAULT_CORNER_RADIUS } else { 0.0 }),
+            ..self.base_params
+        }
+    }
+
+    // loop length to use with a shader, the command line wins over the shader's own //@loop
+    fn loop_time(&self, directives: &shader_check::Directives) -> f32 {
+        self.loop_time.or(directives.loop_time).unwrap_or(playback::DEFAULT_LOOP_TIME)
+    }
+
+    // the same for the playback speed and //@speed
+    fn speed(&self, directives: &shader_check::Directives) -> f32 {
+        self.speed.or(directives.speed).unwrap_or(1.0)
+    }
+
+    // how the image is filtered when it's scaled
+    fn filter(&self) -> wgpu::FilterMode {
+        if self.nearest { wgpu::FilterMode::Nearest } else { wgpu::FilterMode::Linear }
+    }
+
+    // cut an export down to the --channel if there is one
+    fn channel_of(&self, img: image::DynamicImage) -> image::DynamicImage {
+        match self.channel {
+            Some(channel) => export::extract_channel(&img, channel),
+            None => img,
+        }
+    }
+
+    // what the hud calls the effect, the custom shader's file name or built-in
+    fn effect_name(&self) -> String {
+        self.shader.as_deref().and_then(|path| Path::new(path).file_stem()).map_or(String::from("built-in"), |stem| stem.to_string_lossy().to_string())
+    }
+
+    fn export_aspect(&self) -> Option<export::ExportAspect> {
+        self.export_aspect.map(|(width, height)| export::ExportAspect { width, height, fit: self.export_fit })
+    }
+}
+
+// how long the notice after switching filters stays on screen
+const FILTER_NOTICE_SECS: f32 = 1.5;
+
+// how much of a shader error fits on screen, naga's messages can run long
+const SHADER_ERROR_LINES: usize = 12;
+
+fn filter_name(filter: wgpu::FilterMode) -> &'static str {
+    match filter {
+        wgpu::FilterMode::Nearest => "nearest",
+        wgpu::FilterMode::Linear => "linear",
+    }
+}
+
+// parse the value following an option, exiting with a message if it's missing or invalid
+fn parse_value<T: std::str::FromStr>(option: &str, value: Option<String>) -> T {
+    let Some(value) = value else {
+        eprintln!("{} requires a value", option);
         std::process::exit(1);
+    };
+    value.parse().unwrap_or_else(|_| {
+        eprintln!("Invalid value for {}: {}", option, value);
+        std::process::exit(1);
+    })
+}
+
+// turn a backend name into the wgpu backends to try
+fn parse_backend(name: &str) -> wgpu::Backends {
+    match name {
+        "vulkan" => wgpu::Backends::VULKAN,
+        "metal" => wgpu::Backends::METAL,
+        "dx12" => wgpu::Backends::DX12,
+        "gl" => wgpu::Backends::GL,
+        "all" => wgpu::Backends::all(),
+        _ => {
+            eprintln!("Unknown backend: {} (expected vulkan, metal, dx12, gl or all)", name);
+            std::process::exit(1);
+        }
     }
+}
 
-    // Get image path from second arg
-    args[1].clone()
+// what everything is without any options
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            img_path: None,
+            extra_images: Vec::new(),
+            auto_fit: false,
+            native: false,
+            integer_scale: false,
+            nearest: false,
+            loop_time: None,
+            speed: None,
+            raw_delta: false,
+            title: String::from("Balatro Shader Simulation"),
+            mockup: false,
+            transparent: false,
+            tile_preview: false,
+            render_scale: 1.0,
+            max_render_dim: None,
+            downsample: Downsample::Bilinear,
+            backends: wgpu::Backends::all(),
+            gpu: None,
+            oversample: 1,
+            crt_curvature: Params::default().crt_curvature,
+            chromatic_aberration: Params::default().chromatic_aberration,
+            mask: None,
+            mask_strength: Params::default().mask_strength,
+            corner_radius: None,
+            scanline_offset: 0.0,
+            opacity: 1.0,
+            uv_test: false,
+            dither: Dither::Off,
+            #[cfg(feature = "osc-clock")]
+            clock_port: None,
+            #[cfg(feature = "gpu-trace")]
+            profile_dir: None,
+            automation: None,
+            channel: None,
+            base_params: Params::default(),
+            zoom: 1.0,
+            pan: [0.0, 0.0],
+            card_glow: None,
+            glow_strength: 1.0,
+            spritesheet: false,
+            record: false,
+            dump_frame_hashes: false,
+            frames: 16,
+            cols: None,
+            out: None,
+            out_layers: None,
+            burn_hud: false,
+            fps_overlay: false,
+            shader: None,
+            mrt_debug: false,
+            pre_blur: 0.0,
+            pre_blur_iterations: 1,
+            bit_depth: 8,
+            export_aspect: None,
+            export_fit: export::ExportFit::Letterbox,
+            bundle: None,
+            golden: false,
+            sweep: None,
+            sweep_from: None,
+            sweep_to: None,
+            sweep_steps: 5,
+            bezel: 0,
+            bezel_color: None,
+            bezel_texture: None,
+            latency_debug: false,
+            export_params_json: false,
+            ignore_icc: false,
+            reference: None,
+        }
+    }
+}
+
+// what an export made with these options used, `frames` being the params each frame was drawn with
+impl export::ExportInfo {
+    fn from_args(args: &Args, frames: Vec<Params>) -> Self {
+        Self { effect: args.effect_name(), frames, oversample: args.oversample, bit_depth: args.bit_depth }
+    }
+}
+
+// Parse command line arguments
+fn parse_args() -> Args {
+    let mut args = Args::default();
+
+    // skip the binary name and check each remaining arg
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--auto-fit" => args.auto_fit = true,
+            "--native" => args.native = true,
+            "--integer-scale" => args.integer_scale = true,
+            "--nearest" => args.nearest = true,
+            "--loop-time" => {
+                let loop_time: f32 = parse_value(&arg, iter.next());
+                if loop_time <= 0.0 {
+                    eprintln!("--loop-time must be greater than 0");
+                    std::process::exit(1);
+                }
+                args.loop_time = Some(loop_time);
+            }
+            "--speed" => {
+                let speed: f32 = parse_value(&arg, iter.next());
+                if speed <= 0.0 {
+                    eprintln!("--speed must be greater than 0");
+                    std::process::exit(1);
+                }
+                args.speed = Some(speed);
+            }
+            "--zoom" => {
+                args.zoom = parse_value(&arg, iter.next());
+                if !(view::MIN_ZOOM..=view::MAX_ZOOM).contains(&args.zoom) {
+                    eprintln!("--zoom must be between {} and {}", view::MIN_ZOOM, view::MAX_ZOOM);
+                    std::process::exit(1);
+                }
+            }
+            "--pan" => {
+                let pan: String = parse_value(&arg, iter.next());
+                let parsed = pan.split_once(',').and_then(|(x, y)| Some([x.trim().parse().ok()?, y.trim().parse().ok()?]));
+                args.pan = parsed.unwrap_or_else(|| {
+                    eprintln!("Invalid value for --pan: {} (expected x,y in window pixels like 40,-20)", pan);
+                    std::process::exit(1);
+                });
+            }
+            "--raw-delta" => args.raw_delta = true,
+            #[cfg(feature = "osc-clock")]
+            "--clock-port" => args.clock_port = Some(parse_value(&arg, iter.next())),
+            #[cfg(feature = "gpu-trace")]
+            "--profile" => {
+                let kind: String = parse_value(&arg, iter.next());
+                if kind != "gpu" {
+                    eprintln!("Unknown profile {}, expected gpu", kind);
+                    std::process::exit(1);
+                }
+                args.profile_dir.get_or_insert_with(|| String::from(DEFAULT_TRACE_DIR));
+            }
+            #[cfg(feature = "gpu-trace")]
+            "--profile-dir" => args.profile_dir = Some(parse_value(&arg, iter.next())),
+            "--title" => args.title = parse_value(&arg, iter.next()),
+            "--mockup" => args.mockup = true,
+            "--transparent" => args.transparent = true,
+            "--tile-preview" => args.tile_preview = true,
+            "--render-scale" => {
+                args.render_scale = parse_value(&arg, iter.next());
+                if !(args.render_scale > 0.0 && args.render_scale <= 4.0) {
+                    eprintln!("--render-scale must be greater than 0 and at most 4");
+                    std::process::exit(1);
+                }
+            }
+            "--max-render-dim" => {
+                let max_dim: u32 = parse_value(&arg, iter.next());
+                if max_dim == 0 {
+                    eprintln!("--max-render-dim must be at least 1");
+                    std::process::exit(1);
+                }
+                args.max_render_dim = Some(max_dim);
+            }
+            "--downsample" => args.downsample = parse_value(&arg, iter.next()),
+            "--backend" => args.backends = parse_backend(&parse_value::<String>(&arg, iter.next())),
+            "--gpu" => args.gpu = Some(parse_value(&arg, iter.next())),
+            "--oversample-edges" => {
+                args.oversample = parse_value(&arg, iter.next());
+                if !matches!(args.oversample, 1 | 2 | 4) {
+                    eprintln!("--oversample-edges must be 1, 2 or 4");
+                    std::process::exit(1);
+                }
+            }
+            "--crt-curvature" => args.crt_curvature = parse_value(&arg, iter.next()),
+            "--ca" => args.chromatic_aberration = parse_value(&arg, iter.next()),
+            "--mask" => args.mask = Some(parse_value(&arg, iter.next())),
+            "--mask-strength" => args.mask_strength = parse_value(&arg, iter.next()),
+            "--corner-radius" => args.corner_radius = Some(parse_value(&arg, iter.next())),
+            "--scanline-offset" => args.scanline_offset = parse_value(&arg, iter.next()),
+            "--uv-test" => args.uv_test = true,
+            "--opacity" => {
+                args.opacity = parse_value(&arg, iter.next());
+                if !(0.0..=1.0).contains(&args.opacity) {
+                    eprintln!("--opacity must be between 0 and 1");
+                    std::process::exit(1);
+                }
+            }
+            "--dither" => args.dither = parse_value(&arg, iter.next()),
+            "--automation" => args.automation = Some(parse_value(&arg, iter.next())),
+            "--params" => {
+                let path: String = parse_value(&arg, iter.next());
+                let loaded = std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|source| {
+                    let mut params = Params::default();
+                    preset::parse_toml(&source, &mut params).map(|()| params)
+                });
+                let loaded = loaded.unwrap_or_else(|e| {
+                    eprintln!("Failed to load params from {}: {}", path, e);
+                    std::process::exit(1);
+                });
+                // options after --params still override it, so the ones with their own option are copied over too
+                args.crt_curvature = loaded.crt_curvature;
+                args.chromatic_aberration = loaded.chromatic_aberration;
+                args.mask_strength = loaded.mask_strength;
+                args.corner_radius = Some(loaded.corner_radius);
+                args.scanline_offset = loaded.scanline_offset;
+                args.opacity = loaded.global_opacity;
+                args.base_params = loaded;
+            }
+            "--card-glow" => {
+                let color: String = parse_value(&arg, iter.next());
+                args.card_glow = Some(glow::parse_glow_color(&color).unwrap_or_else(|| {
+                    eprintln!("Invalid value for --card-glow: {} (expected common, uncommon, rare, legendary or a hex colour)", color);
+                    std::process::exit(1);
+                }));
+            }
+            "--glow-strength" => args.glow_strength = parse_value(&arg, iter.next()),
+            "--spritesheet" => args.spritesheet = true,
+            "--record" => args.record = true,
+            "--dump-frame-hashes" => args.dump_frame_hashes = true,
+            "--golden" => args.golden = true,
+            "--sweep" => {
+                let name: String = parse_value(&arg, iter.next());
+                args.sweep = Some(render::SWEEPABLE.iter().position(|&(param, _)| param == name).unwrap_or_else(|| {
+                    let names: Vec<&str> = render::SWEEPABLE.iter().map(|&(param, _)| param).collect();
+                    eprintln!("Can't sweep {}, expected one of {}", name, names.join(", "));
+                    std::process::exit(1);
+                }));
+            }
+            "--from" => args.sweep_from = Some(parse_value(&arg, iter.next())),
+            "--to" => args.sweep_to = Some(parse_value(&arg, iter.next())),
+            "--steps" => {
+                args.sweep_steps = parse_value(&arg, iter.next());
+                if args.sweep_steps < 2 {
+                    eprintln!("--steps must be at least 2");
+                    std::process::exit(1);
+                }
+            }
+            "--frames" => args.frames = parse_value(&arg, iter.next()),
+            "--cols" => args.cols = Some(parse_value(&arg, iter.next())),
+            "--out" => args.out = Some(parse_value(&arg, iter.next())),
+            "--out-layers" => args.out_layers = Some(parse_value(&arg, iter.next())),
+            "--export-params-json" => args.export_params_json = true,
+            "--export-aspect" => {
+                let aspect: String = parse_value(&arg, iter.next());
+                args.export_aspect = Some(export::parse_aspect(&aspect).unwrap_or_else(|| {
+                    eprintln!("Invalid value for --export-aspect: {} (expected width:height like 16:9)", aspect);
+                    std::process::exit(1);
+                }));
+            }
+            "--export-fit" => args.export_fit = parse_value(&arg, iter.next()),
+            "--channel" => args.channel = Some(parse_value(&arg, iter.next())),
+            "--burn-hud" => args.burn_hud = true,
+            "--fps-overlay" => args.fps_overlay = true,
+            "--latency-debug" => args.latency_debug = true,
+            "--shader" => args.shader = Some(parse_value(&arg, iter.next())),
+            "--mrt-debug" => args.mrt_debug = true,
+            "--bit-depth" => {
+                args.bit_depth = parse_value(&arg, iter.next());
+                if !matches!(args.bit_depth, 8 | 16) {
+                    eprintln!("--bit-depth must be 8 or 16");
+                    std::process::exit(1);
+                }
+            }
+            "--ignore-icc" => args.ignore_icc = true,
+            "--reference" => args.reference = Some(parse_value(&arg, iter.next())),
+            "--bezel" => args.bezel = parse_value(&arg, iter.next()),
+            "--bezel-color" => {
+                let hex: String = parse_value(&arg, iter.next());
+                args.bezel_color = Some(bezel::parse_color(&hex).unwrap_or_else(|| {
+                    eprintln!("Invalid value for --bezel-color: {} (expected a hex colour like 2b3a3f)", hex);
+                    std::process::exit(1);
+                }));
+            }
+            "--bezel-texture" => args.bezel_texture = Some(parse_value(&arg, iter.next())),
+            "--bundle" => args.bundle = Some(parse_value(&arg, iter.next())),
+            "--pre-blur" => {
+                args.pre_blur = parse_value(&arg, iter.next());
+                if !(0.0..=blur::MAX_RADIUS).contains(&args.pre_blur) {
+                    eprintln!("--pre-blur must be between 0 and {}", blur::MAX_RADIUS);
+                    std::process::exit(1);
+                }
+            }
+            "--pre-blur-iterations" => {
+                args.pre_blur_iterations = parse_value(&arg, iter.next());
+                if !(1..=blur::MAX_ITERATIONS).contains(&args.pre_blur_iterations) {
+                    eprintln!("--pre-blur-iterations must be between 1 and {}", blur::MAX_ITERATIONS);
+                    std::process::exit(1);
+                }
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option: {}", arg);
+                std::process::exit(1);
+            }
+            _ if args.img_path.is_none() => args.img_path = Some(arg),
+            _ => args.extra_images.push(arg),
+        }
+    }
+
+    if args.frames == 0 || args.cols == Some(0) {
+        eprintln!("--frames and --cols must be greater than 0");
+        std::process::exit(1);
+    }
+    if args.channel.is_some() && !args.spritesheet && args.sweep.is_none() && !args.record {
+        eprintln!("--channel only applies to --spritesheet, --sweep and --record");
+        std::process::exit(1);
+    }
+    if args.sweep.is_some() && (args.sweep_from.is_none() || args.sweep_to.is_none()) {
+        eprintln!("--sweep needs --from and --to");
+        std::process::exit(1);
+    }
+
+    // a bundle provides the image and (unless one was passed separately) the shader
+    if let Some(path) = &args.bundle {
+        if args.img_path.is_some() {
+            eprintln!("Pass either an image or --bundle, not both");
+            std::process::exit(1);
+        }
+        let bundle = bundle::extract(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+        println!("Extracted bundle {} to {}", path, bundle.image.parent().unwrap_or(Path::new("")).display());
+        args.img_path = Some(bundle.image.to_string_lossy().to_string());
+        if args.shader.is_none() {
+            args.shader = bundle.shader.map(|shader| shader.to_string_lossy().to_string());
+        }
+    }
+
+    args
 }
 
 // Open a file dialog using rfd
@@ -66,7 +570,7 @@ fn pick_image_file() -> String {
     }
 }
 
-fn load_image(img_path: &str) -> Result<RgbaImage, image::ImageError> {
+fn load_image(img_path: &str, ignore_icc: bool) -> Result<RgbaImage, image::ImageError> {
     if !Path::new(img_path).exists() {
         return Err(image::ImageError::IoError(std::io::Error::new(
             std::io::ErrorKind::NotFound,
@@ -74,16 +578,556 @@ fn load_image(img_path: &str) -> Result<RgbaImage, image::ImageError> {
         )));
     }
 
-    let img_dynamic = image::open(img_path)?;
-    Ok(img_dynamic.to_rgba8())
+    // read any embedded colour profile before decoding, the decoder is used up by decoding
+    let mut decoder = image::ImageReader::open(img_path)?.with_guessed_format()?.into_decoder()?;
+    let icc = decoder.icc_profile()?;
+    let mut img = image::DynamicImage::from_decoder(decoder)?.to_rgba8();
+
+    if let Some(icc) = icc {
+        if ignore_icc {
+            eprintln!("{} has an embedded colour profile, ignoring it (--ignore-icc)", img_path);
+        } else if let Err(e) = apply_icc_profile(&mut img, &icc) {
+            eprintln!("{} has an embedded colour profile that couldn't be applied, colours may look off: {}", img_path, e);
+        }
+    }
+
+    Ok(img)
+}
+
+// convert from an embedded icc profile to srgb, which is what the shader (and the game) assume
+fn apply_icc_profile(img: &mut RgbaImage, icc: &[u8]) -> Result<(), moxcms::CmsError> {
+    let profile = moxcms::ColorProfile::new_from_slice(icc)?;
+    let srgb = moxcms::ColorProfile::new_srgb();
+    let transform = profile.create_transform_8bit(moxcms::Layout::Rgba, &srgb, moxcms::Layout::Rgba, Default::default())?;
+    let src = img.as_raw().clone();
+    transform.transform(&src, img)
 }
 
+// find the bounding box of all non-transparent pixels, or None if the image is fully transparent
+fn opaque_bounds(img: &RgbaImage) -> Option<Bounds> {
+    let (mut min_x, mut min_y) = (u32::MAX, u32::MAX);
+    let (mut max_x, mut max_y) = (0, 0);
+
+    for (x, y, pixel) in img.enumerate_pixels() {
+        if pixel[3] > 0 {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+
+    if min_x > max_x {
+        return None;
+    }
+    Some((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+}
+
+// read a custom shader from disk
+fn load_shader(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read shader {}: {}", path, e))
+}
+
+// with --mrt-debug the shader has to provide the entry point that writes the debug output
+fn check_debug_entry(args: &Args, source: String) -> Result<String, String> {
+    if args.mrt_debug && !shader_check::has_entry_point(&source, "fs_debug") {
+        return Err(String::from("--mrt-debug needs the shader to have an `fs_debug` entry point (see shaders.wgsl)"));
+    }
+    Ok(source)
+}
+
+// the shader to start with, the custom one if there is one or the built-in one otherwise
+fn initial_shader(args: &Args) -> String {
+    let source = match &args.shader {
+        Some(path) => load_shader(path),
+        None => Ok(SHADER_SOURCE.to_string()),
+    };
+    source.and_then(|source| check_debug_entry(args, source)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+// fill `target` with `color`, for anything that's then drawn over with blending
+fn clear(encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView, color: wgpu::Color) {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Clear Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations { load: wgpu::LoadOp::Clear(color), store: wgpu::StoreOp::Store },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+}
+
+// set up the renderer with the shader and image settings from the command line
+fn create_renderer(
+    args: &Args,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    backend: wgpu::Backend,
+    img: &RgbaImage,
+    shader_source: &str,
+) -> Renderer {
+    let mut renderer = Renderer::new(device, queue, backend, img, shader_source).unwrap_or_else(|e| {
+        eprintln!("Shader error: {}", e);
+        std::process::exit(1);
+    });
+    renderer.set_pre_blur(args.pre_blur);
+    renderer.set_blur_iterations(args.pre_blur_iterations);
+    renderer.set_filter(device, args.filter());
+    if let Some(path) = &args.mask {
+        let mask = load_image(path, args.ignore_icc).unwrap_or_else(|e| {
+            eprintln!("Failed to load mask {}: {}", path, e);
+            std::process::exit(1);
+        });
+        renderer.set_mask(device, queue, &mask);
+    }
+    renderer
+}
+
+// pick up the loop length and speed a newly loaded shader asks for
+fn apply_directives(args: &Args, playback: &mut Playback, shader_source: &str) {
+    let directives = shader_check::directives(shader_source);
+    let (loop_time, speed) = (args.loop_time(&directives), args.speed(&directives));
+    if loop_time != playback.loop_time || speed != playback.speed {
+        println!("Loop time: {:.2} s, speed: {:.2}x", loop_time, speed);
+    }
+    playback.loop_time = loop_time;
+    playback.time %= loop_time;
+    playback.speed = speed;
+}
+
+// create a device interface and queue for the selected gpu
+fn request_device(args: &Args, adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    let device = pollster::block_on(
+        adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+                experimental_features: Default::default(),
+                memory_hints: Default::default(),
+                trace: gpu_trace(args),
+            },
+        )
+    ).expect("Failed to create device");
+    check_gpu_trace(args);
+    device
+}
+
+// where --profile gpu writes the trace if there's no --profile-dir
+#[cfg(feature = "gpu-trace")]
+const DEFAULT_TRACE_DIR: &str = "gpu-trace";
+
+// trace into the --profile-dir if profiling was asked for, otherwise nothing gets written
+#[cfg(feature = "gpu-trace")]
+fn gpu_trace(args: &Args) -> wgpu::Trace {
+    let Some(dir) = &args.profile_dir else {
+        return wgpu::Trace::Off;
+    };
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        eprintln!("Failed to create trace directory {}: {}", dir, e);
+        std::process::exit(1);
+    }
+    println!("Writing a wgpu trace to {}", dir);
+    wgpu::Trace::Directory(dir.into())
+}
+
+// wgpu itself says the trace parameter does nothing, but it's passed on to wgpu-core which writes the trace
+// when its trace feature is on. the trace file is started with the device, so make sure it really was
+#[cfg(feature = "gpu-trace")]
+fn check_gpu_trace(args: &Args) {
+    let Some(dir) = &args.profile_dir else {
+        return;
+    };
+    if !Path::new(dir).join("trace.ron").is_file() {
+        eprintln!("wgpu didn't start a trace in {}, this build of wgpu can't record them", dir);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "gpu-trace"))]
+fn gpu_trace(_args: &Args) -> wgpu::Trace {
+    wgpu::Trace::Off
+}
+
+#[cfg(not(feature = "gpu-trace"))]
+fn check_gpu_trace(_args: &Args) {}
+
+// set up a gpu without a window for exports, and say which backend it's on
+fn headless_device(args: &Args) -> (wgpu::Device, wgpu::Queue, wgpu::Backend) {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: args.backends,
+        ..Default::default()
+    });
+
+    // no window, so any adapter will do
+    let adapter = pick_adapter(args, &instance, None);
+    let (device, queue) = request_device(args, &adapter);
+    (device, queue, adapter.get_info().backend)
+}
+
+// the gpu, renderer and everything else an export draws with, set up from the command line
+struct Headless {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    renderer: Renderer,
+    // draws the hud, or the labels of a sweep
+    text: Option<text::TextRenderer>,
+    automation: Option<automation::Automation>,
+    effect: String,
+    // from --loop-time, or what the shader asks for
+    loop_time: f32,
+}
+
+impl Headless {
+    fn new(args: &Args, img: &RgbaImage) -> Self {
+        let (device, queue, backend) = headless_device(args);
+        let shader_source = initial_shader(args);
+        let renderer = create_renderer(args, &device, &queue, backend, img, &shader_source);
+        let text = (args.burn_hud || args.sweep.is_some()).then(|| text::TextRenderer::new(&device, &queue));
+        Self {
+            device,
+            queue,
+            renderer,
+            text,
+            automation: load_automation(args),
+            effect: args.effect_name(),
+            loop_time: args.loop_time(&shader_check::directives(&shader_source)),
+        }
+    }
+
+    // an exporter for the final colour with the export options from the command line
+    fn exporter(&mut self, args: &Args) -> export::Exporter<'_> {
+        export::Exporter {
+            device: &self.device,
+            queue: &self.queue,
+            renderer: &mut self.renderer,
+            oversample: args.oversample,
+            text: self.text.as_mut(),
+            burn_hud: args.burn_hud,
+            effect: &self.effect,
+            caption: None,
+            debug_channel: false,
+            bit_depth: args.bit_depth,
+            aspect: args.export_aspect(),
+            automation: self.automation.as_ref(),
+        }
+    }
+}
+
+// the adapter picked with --gpu, or the best one wgpu finds if there's no --gpu
+fn pick_adapter(args: &Args, instance: &wgpu::Instance, surface: Option<&wgpu::Surface>) -> wgpu::Adapter {
+    let Some(wanted) = &args.gpu else {
+        return pollster::block_on(
+            instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: surface,
+                force_fallback_adapter: false,
+            })
+        ).expect("Failed to find an appropriate adapter");
+    };
+
+    // an index into the list, otherwise a case insensitive match on the name
+    let adapters = instance.enumerate_adapters(args.backends);
+    let found = match wanted.parse::<usize>() {
+        Ok(index) => adapters.get(index),
+        Err(_) => adapters.iter().find(|adapter| adapter.get_info().name.to_lowercase().contains(&wanted.to_lowercase())),
+    };
+    let Some(adapter) = found else {
+        eprintln!("No gpu matches --gpu {}, the available ones are:", wanted);
+        for (index, adapter) in adapters.iter().enumerate() {
+            let info = adapter.get_info();
+            eprintln!("  {}: {} ({:?}, {:?})", index, info.name, info.backend, info.device_type);
+        }
+        std::process::exit(1);
+    };
+
+    if let Some(surface) = surface {
+        if !adapter.is_surface_supported(surface) {
+            eprintln!("{} can't draw to this window, try another --gpu", adapter.get_info().name);
+            std::process::exit(1);
+        }
+    }
+    adapter.clone()
+}
+
+// render the animation loop into a sprite sheet without opening a window
+fn export_spritesheet(args: &Args, img: &RgbaImage) {
+    let mut headless = Headless::new(args, img);
+    let loop_time = headless.loop_time;
+
+    // default to a roughly square grid
+    let frames = args.frames;
+    let cols = args.cols.unwrap_or_else(|| (frames as f32).sqrt().ceil() as u32);
+    let params = args.initial_params();
+    let mut exporter = headless.exporter(args);
+    let (sheet, used) = exporter.render_spritesheet(&params, loop_time, frames, cols);
+    let sheet = args.channel_of(sheet);
+
+    let out = args.out.as_deref().unwrap_or("spritesheet.png");
+    save_sheet(&sheet, out, frames, cols);
+
+    let info = export::ExportInfo::from_args(args, used);
+    if args.export_params_json {
+        save_sidecar(&info, out);
+    }
+
+    // the shader's debug output gets a sheet of its own next to the normal one
+    if args.mrt_debug {
+        exporter.debug_channel = true;
+        let debug_sheet = args.channel_of(exporter.render_spritesheet(&params, loop_time, frames, cols).0);
+        let path = Path::new(out);
+        let stem = path.file_stem().map_or(String::from("spritesheet"), |stem| stem.to_string_lossy().to_string());
+        let debug_out = path.with_file_name(format!("{}-debug.png", stem));
+        save_sheet(&debug_sheet, &debug_out.to_string_lossy(), frames, cols);
+        if args.export_params_json {
+            save_sidecar(&info, &debug_out.to_string_lossy());
+        }
+    }
+}
+
+fn load_automation(args: &Args) -> Option<automation::Automation> {
+    args.automation.as_ref().map(|path| automation::Automation::load(path).unwrap_or_else(|e| {
+        eprintln!("Failed to load automation {}: {}", path, e);
+        std::process::exit(1);
+    }))
+}
+
+// save what's been recorded with K next to where the viewer was run, named after `img_path`
+fn save_recording(recorder: automation::Recorder, img_path: &str) {
+    let stem = Path::new(img_path).file_stem().map_or(String::from("image"), |stem| stem.to_string_lossy().to_string());
+    let path = format!("{}-automation.csv", stem);
+    match recorder.save(&path) {
+        Ok(keys) => println!("Saved {} keyframes to {}, play them back with --automation {}", keys, path, path),
+        Err(e) => eprintln!("Failed to save automation: {}", e),
+    }
+}
+
+// render the animation loop into an animated webp (or gif, by the extension of --out) without opening a window
+fn export_recording(args: &Args, img: &RgbaImage) {
+    let mut headless = Headless::new(args, img);
+    let loop_time = headless.loop_time;
+    let params = args.initial_params();
+    let mut exporter = headless.exporter(args);
+    // a --channel recording is grayscale, kept as rgba since that's what the encoders take
+    let (frames, used): (Vec<RgbaImage>, Vec<Params>) = exporter
+        .render_loop(&params, loop_time, args.frames)
+        .into_iter()
+        .map(|(frame, params)| (args.channel_of(frame).to_rgba8(), params))
+        .unzip();
+
+    // each frame lasts its share of the loop, so it plays back at the speed it was made at
+    let delay_ms = (loop_time * 1000.0 / args.frames as f32).round().max(1.0) as u32;
+    let out = args.out.clone().unwrap_or_else(|| String::from("loop.webp"));
+    let is_gif = Path::new(&out).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+    #[cfg(feature = "webp")]
+    let saved = if is_gif { anim::save_gif(&frames, delay_ms, &out).map(|()| out) } else { anim::save_webp(&frames, delay_ms, &out).map(|()| out) };
+    #[cfg(not(feature = "webp"))]
+    let saved = {
+        let out = if is_gif {
+            out
+        } else {
+            let gif = Path::new(&out).with_extension("gif").to_string_lossy().to_string();
+            eprintln!("Built without the webp feature, saving a gif to {} instead (256 colours, no soft alpha)", gif);
+            gif
+        };
+        anim::save_gif(&frames, delay_ms, &out).map(|()| out)
+    };
+
+    let out = match saved {
+        Ok(out) => out,
+        Err(e) => {
+            eprintln!("Failed to save recording: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Saved {} frames at {} ms each to {}", frames.len(), delay_ms, out);
+
+    if args.export_params_json {
+        save_sidecar(&export::ExportInfo::from_args(args, used), &out);
+    }
+}
+
+// render the loop like --spritesheet and print a hash of each frame, so runs on different machines, backends or
+// builds can be diffed without comparing images. times are fixed steps through the loop, never the clock
+fn dump_frame_hashes(args: &Args, img: &RgbaImage) {
+    let mut headless = Headless::new(args, img);
+    let loop_time = headless.loop_time;
+    let params = args.initial_params();
+    let mut exporter = headless.exporter(args);
+
+    // one line per frame on stdout so two runs can be compared with diff
+    for (frame, params) in exporter.render_loop(&params, loop_time, args.frames) {
+        println!("{} {:.6} {:016x}", params.frame, params.time, export::frame_hash(&frame));
+    }
+}
+
+// render the first frame of the loop and save it with the original image as layers of a tiff
+fn export_layers(args: &Args, img: &RgbaImage, out: &str) {
+    let mut headless = Headless::new(args, img);
+    let params = args.initial_params();
+    let shaded = headless.exporter(args).render_frame(&params);
+
+    // the original is fitted the same way so the layers still line up
+    let original = image::DynamicImage::ImageRgba8(img.clone());
+    let original = match args.export_aspect() {
+        Some(aspect) => aspect.apply(&original),
+        None => original,
+    };
+    if let Err(e) = export::write_layers(out, &[("original", &original), ("shaded", &shaded)]) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    println!("Saved original and shaded layers to {}", out);
+
+    if args.export_params_json {
+        save_sidecar(&export::ExportInfo::from_args(args, vec![params]), out);
+    }
+}
+
+// render the image once for each step of one param and lay the frames out in a labelled grid
+fn export_sweep(args: &Args, img: &RgbaImage, param: (&str, &str)) {
+    let mut headless = Headless::new(args, img);
+
+    let (from, to) = (args.sweep_from.unwrap_or_default(), args.sweep_to.unwrap_or_default());
+    let steps = args.sweep_steps;
+    let values: Vec<f32> = (0..steps).map(|i| from + (to - from) * i as f32 / (steps - 1) as f32).collect();
+    let cols = args.cols.unwrap_or_else(|| (steps as f32).sqrt().ceil() as u32);
+
+    let sheet = args.channel_of(headless.exporter(args).render_sweep(&args.initial_params(), param, &values, cols));
+
+    let out = args.out.as_deref().unwrap_or("sweep.png");
+    match sheet.save(out) {
+        Ok(()) => println!("Saved {} steps of {} from {} to {} to {}", steps, param.0, from, to, out),
+        Err(e) => {
+            eprintln!("Failed to save sweep: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// render the built-in shader on the built-in sample card with fixed settings, for documentation images
+// that come out the same everywhere. only the backend and output path can be changed
+fn export_golden(args: &Args) {
+    // every other option is left at its default
+    let golden_args = Args {
+        backends: args.backends,
+        gpu: args.gpu.clone(),
+        #[cfg(feature = "gpu-trace")]
+        profile_dir: args.profile_dir.clone(),
+        ..Args::default()
+    };
+    let mut headless = Headless::new(&golden_args, &golden::sample_card());
+    let params = golden::golden_params();
+    let frame = headless.exporter(&golden_args).render_frame(&params);
+
+    let out = args.out.as_deref().unwrap_or(golden::GOLDEN_OUT);
+    match frame.save(out) {
+        Ok(()) => println!("Saved golden frame to {}", out),
+        Err(e) => {
+            eprintln!("Failed to save golden frame: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if args.export_params_json {
+        save_sidecar(&export::ExportInfo::from_args(&golden_args, vec![params]), out);
+    }
+}
+
+// write the params json next to an exported image
+fn save_sidecar(info: &export::ExportInfo, out: &str) {
+    match info.write_sidecar(out) {
+        Ok(path) => println!("Saved params to {}", path.display()),
+        Err(e) => {
+            eprintln!("Failed to save params json: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn save_sheet(sheet: &image::DynamicImage, out: &str, frames: u32, cols: u32) {
+    match sheet.save(out) {
+        Ok(()) => println!("Saved {} frames ({} columns) to {}", frames, cols, out),
+        Err(e) => {
+            eprintln!("Failed to save sprite sheet: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// winit 0.30 deprecates the closure based event loop, but it keeps everything below in one place
+#[allow(deprecated)]
 fn main() {
 
+    // use the image passed on the command line, or ask for one
+    let args = parse_args();
+
+    // the golden frame brings its own image
+    if args.golden {
+        export_golden(&args);
+        return;
+    }
+
+    let img_path = args.img_path.clone().unwrap_or_else(pick_image_file);
+
     // Load and store image
-    let img_path = pick_image_file();
-    let img = load_image(&img_path).expect("Failed to load image");
+    let img = load_image(&img_path, args.ignore_icc).expect("Failed to load image");
     let (width, height) = (img.width(), img.height());
+    let img_size = (width, height);
+    let mut bounds = opaque_bounds(&img);
+
+    // every image is loaded up front so switching between them is instant, the first one is used for exports
+    let img_paths: Vec<String> = std::iter::once(img_path).chain(args.extra_images.iter().cloned()).collect();
+    let extra_imgs: Vec<RgbaImage> = args.extra_images.iter().map(|path| {
+        let extra = load_image(path, args.ignore_icc).unwrap_or_else(|e| {
+            eprintln!("Failed to load image {}: {}", path, e);
+            std::process::exit(1);
+        });
+        if extra.dimensions() != img_size {
+            eprintln!("{} is {}x{} but {} is {}x{}, all images must be the same size", path, extra.width(), extra.height(), img_paths[0], width, height);
+            std::process::exit(1);
+        }
+        extra
+    }).collect();
+    let mut image_bounds: Vec<Option<Bounds>> = std::iter::once(bounds).chain(extra_imgs.iter().map(opaque_bounds)).collect();
+
+    // exports don't need a window
+    if args.spritesheet {
+        export_spritesheet(&args, &img);
+        return;
+    }
+    if args.record {
+        export_recording(&args, &img);
+        return;
+    }
+    if args.dump_frame_hashes {
+        dump_frame_hashes(&args, &img);
+        return;
+    }
+    if let Some(out) = &args.out_layers {
+        export_layers(&args, &img, out);
+        return;
+    }
+    if let Some(index) = args.sweep {
+        export_sweep(&args, &img, render::SWEEPABLE[index]);
+        return;
+    }
+
+    let mut view_settings = ViewSettings {
+        auto_fit: args.auto_fit,
+        native: args.native,
+        integer_scale: args.integer_scale,
+        zoom: args.zoom,
+        pan: args.pan,
+        bezel: args.bezel,
+    };
 
     // create an event loop
     let event_loop = EventLoop::new().expect("Failed to create event loop");
@@ -95,22 +1139,47 @@ fn main() {
     let mut watcher: RecommendedWatcher =
         Watcher::new(tx, Config::default()).expect("Failed to create watcher");
 
-    // start watching file
-    watcher.watch((&img_path).as_ref(), RecursiveMode::NonRecursive)
-        .expect("Failed to watch file");
+    // start watching the images, remembering where each one is so a change can be matched back to it
+    let image_watch_paths: Vec<std::path::PathBuf> = img_paths.iter().map(|path| {
+        watcher.watch(path.as_ref(), RecursiveMode::NonRecursive)
+            .expect("Failed to watch file");
+        std::fs::canonicalize(path).unwrap_or_else(|_| path.into())
+    }).collect();
 
-    // build our viewport with the image size in mind
+    // and the custom shader, so edits to it show up straight away too
+    let shader_path = args.shader.as_ref().map(|path| std::fs::canonicalize(path).unwrap_or_else(|_| path.into()));
+    if let Some(path) = &shader_path {
+        watcher.watch(path, RecursiveMode::NonRecursive)
+            .expect("Failed to watch shader");
+    }
+
+    // build our viewport with the image size in mind (or just the artwork if we're auto-fitting)
+    let (view_width, view_height) = match bounds {
+        Some((_, _, w, h)) if view_settings.auto_fit => (w, h),
+        _ => (width, height),
+    };
+    let (view_width, view_height) = (view_width + 2 * args.bezel, view_height + 2 * args.bezel);
     let window_attributes = Window::default_attributes()
-        .with_title("Balatro Shader Simulation")
-        .with_inner_size(winit::dpi::LogicalSize::new(width as f64, height as f64));
+        .with_title(&args.title)
+        .with_inner_size(winit::dpi::PhysicalSize::new(view_width, view_height))
+        .with_transparent(args.transparent)
+        // stays hidden until the shader's warmed up, so the first frame shown isn't a stutter
+        .with_visible(false);
     
     let window = event_loop.create_window(window_attributes)
         .expect("Failed to create window");
+
+    // on scaled displays a logical size can land on half pixels (71x95 at 150% is 106.5x142.5) which blurs
+    // the art, so size the window in physical pixels using the nearest whole multiple of the scale factor
+    let pixel_scale = window.scale_factor().round().max(1.0) as u32;
+    if pixel_scale > 1 {
+        let _ = window.request_inner_size(winit::dpi::PhysicalSize::new(view_width * pixel_scale, view_height * pixel_scale));
+    }
     let window = Arc::new(window);
 
     // create a gpu instance (this represents the direct connection to the hardware)
     let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-        backends: wgpu::Backends::all(),
+        backends: args.backends,
         ..Default::default()
     });
 
@@ -119,39 +1188,43 @@ fn main() {
         .expect("Failed to create surface");
 
     // looks for a gpu that's compatible with our needs
-    let adapter = pollster::block_on(
-        instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        })
-    ).expect("Failed to find an appropriate adapter");
+    let adapter = pick_adapter(&args, &instance, Some(&surface));
 
-    // create a device interface and queue for the selected gpu
-    let (device, queue) = pollster::block_on(
-        adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                label: None,
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
-                experimental_features: Default::default(),
-                memory_hints: Default::default(),
-                trace: Default::default(),
-            },
-        )
-    ).expect("Failed to create device");
+    let (device, queue) = request_device(&args, &adapter);
 
     // select a supported surface format and alpha mode (just pick the first one if there are multiple)
+    // some broken driver setups report a surface we can't actually draw to
     let caps = surface.get_capabilities(&adapter);
-    let surface_format = caps.formats[0];
-    let surface_alpha_mode = caps.alpha_modes[0];
+    let (Some(&surface_format), Some(&surface_alpha_mode)) = (caps.formats.first(), caps.alpha_modes.first()) else {
+        eprintln!(
+            "The {:?} backend on {} can't present to this window (no supported surface formats). \
+             Try a different backend with --backend, e.g. --backend vulkan or --backend gl.",
+            adapter.get_info().backend,
+            adapter.get_info().name,
+        );
+        std::process::exit(1);
+    };
+
+    // a see-through window needs the compositor to use our alpha. drawing over a transparent clear with alpha
+    // blending leaves premultiplied colour behind, so that's the mode to ask for
+    let surface_alpha_mode = if args.transparent {
+        let modes = [wgpu::CompositeAlphaMode::PreMultiplied, wgpu::CompositeAlphaMode::PostMultiplied];
+        modes.into_iter().find(|mode| caps.alpha_modes.contains(mode)).unwrap_or_else(|| {
+            eprintln!("This window can't be see-through on {} ({:?} only), --transparent is ignored", adapter.get_info().name, caps.alpha_modes);
+            surface_alpha_mode
+        })
+    } else {
+        surface_alpha_mode
+    };
+    let see_through = args.transparent && matches!(surface_alpha_mode, wgpu::CompositeAlphaMode::PreMultiplied | wgpu::CompositeAlphaMode::PostMultiplied);
 
-    // configure the surface to the chosen device
+    // configure the surface to the chosen device, at whatever size the window actually opened at
+    let inner_size = window.inner_size();
     let mut config = wgpu::SurfaceConfiguration {
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
         format: surface_format,
-        width,
-        height,
+        width: inner_size.width.max(1),
+        height: inner_size.height.max(1),
         present_mode: wgpu::PresentMode::Fifo,
         desired_maximum_frame_latency: 0,
         alpha_mode: surface_alpha_mode,
@@ -159,196 +1232,126 @@ fn main() {
     };
     surface.configure(&device, &config);
 
-    // create our image texture ready to be rendered
-    let texture_size = wgpu::Extent3d {
-        width,
-        height,
-        depth_or_array_layers: 1,
-    };
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-        size: texture_size,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8UnormSrgb,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        label: Some("image_texture"),
-        view_formats: Default::default(),
-    });
+    // set up everything needed to draw the image
+    let shader_source = initial_shader(&args);
+    let mut renderer = create_renderer(&args, &device, &queue, adapter.get_info().backend, &img, &shader_source);
+    for extra in &extra_imgs {
+        renderer.add_image(&device, &queue, extra);
+    }
+    renderer.warm_up(&device, &queue, surface_format, args.mrt_debug);
+    window.set_visible(true);
 
-    // write this texture to our device
-    write_texture(&queue, &texture, &img_path, height, width);
+    // whether the custom --shader is in use, or the built-in one for comparison
+    let mut using_custom_shader = args.shader.is_some();
 
-    // create a sampler to tell the adapter how to handle the texture it's been given
-    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        label: Some("image_sampler"),
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        ..Default::default()
+    // time gets updated from the playback clock every frame
+    let directives = shader_check::directives(&shader_source);
+    let mut playback = Playback::new(args.loop_time(&directives), !args.raw_delta);
+    playback.speed = args.speed(&directives);
+    let mut params = args.initial_params();
+
+    // params played back over the loop, or recorded while K is on (which pauses the playback)
+    let automation = load_automation(&args);
+    let mut recorder: Option<automation::Recorder> = None;
+
+    // params kept in slots with shift and a number key, recalled with the number key in snapshot mode (C)
+    let snapshot_stem = Path::new(&img_paths[0]).file_stem().map_or(String::from("image"), |stem| stem.to_string_lossy().to_string());
+    let snapshot_path = format!("{}-snapshots.json", snapshot_stem);
+    let mut snapshots = snapshot::Snapshots::load(&snapshot_path).unwrap_or_else(|e| {
+        eprintln!("Ignoring saved snapshots, {}", e);
+        snapshot::Snapshots::default()
     });
+    let mut snapshot_mode = false;
+    let mut modifiers = ModifiersState::empty();
 
-    // define params struct
-    #[repr(C)]
-    #[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
-    struct Params {
-        time: f32,
-        artifact_amplifier: f32,
-        crt_amount_adjusted: f32,
-        bloom_fac: f32,
-    }
-
-    // create a buffer to store our params in
-    let params = Params {
-        time: 0.0,
-        artifact_amplifier: 1.0,
-        crt_amount_adjusted: 1.0,
-        bloom_fac: 1.0,
-    };
-    let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Params Buffer"),
-        contents: bytemuck::bytes_of(&params),
-        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    // an outside clock sets the time whenever it sends one, the internal clock carries on in between
+    #[cfg(feature = "osc-clock")]
+    let mut external_clock = args.clock_port.map(|port| {
+        let clock = clock::ExternalClock::bind(port).unwrap_or_else(|e| {
+            eprintln!("Failed to listen for the clock on port {}: {}", port, e);
+            std::process::exit(1);
+        });
+        println!("Listening for the time on udp port {}", port);
+        clock
     });
 
-    // describes what resources we want the shader to access by creating bindings
-    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("texture_bind_group_layout"),
-        entries: &[
-            // binding 0: texture
-            wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    multisampled: false,
-                    view_dimension: wgpu::TextureViewDimension::D2,
-                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                },
-                count: None,
-            },
+    // the view is recalculated before the next frame whenever anything marks it dirty
+    let mut view_dirty = true;
+    let mut current_view = render::FULL_VIEW;
 
-            // binding 1: sampler
-            wgpu::BindGroupLayoutEntry {
-                binding: 1,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                count: None,
-            },
+    // supersampling (or undersampling) renders the scene to a scaled target first
+    let mut composite = Composite::new(&device, surface_format, args.render_scale, args.max_render_dim, args.downsample);
 
-            // binding 2: uniform buffer (Params)
-            wgpu::BindGroupLayoutEntry {
-                binding: 2,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: wgpu::BufferSize::new(
-                        std::mem::size_of::<Params>() as _
-                    ),
-                },
-                count: None,
-            }
-        ],
+    // side by side (or difference) comparison against a reference image, this replaces the mockup and bezel
+    let mut compare = args.reference.as_ref().map(|path| {
+        let reference = load_image(path, args.ignore_icc).unwrap_or_else(|e| {
+            eprintln!("Failed to load reference image {}: {}", path, e);
+            std::process::exit(1);
+        });
+        compare::Compare::new(&device, &queue, surface_format, img_size, &reference, renderer.filter())
     });
 
-    // tie the texture and sampler to the layout's bindings we defined above
-    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("texture_bind_group"),
-        layout: &bind_group_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(&texture_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::Sampler(&sampler),
-            },
-            wgpu::BindGroupEntry {
-                binding: 2,
-                resource: uniform_buffer.as_entire_binding(),
-            },
-        ],
-    });
+    // the custom shader next to (or against) its version from before the last reload, while that's on it
+    // takes the place of --reference
+    let mut previous_compare = compare::Compare::rendered(&device, surface_format, img_size, renderer.filter());
+    let mut show_previous = false;
 
-    // define vertex data for a quad
-    #[repr(C)]
-    #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-    struct Vertex {
-        position: [f32; 2],
-        uv: [f32; 2],
-    }
-    let vertices = [
-        Vertex { position: [-1.0, -1.0], uv: [0.0, 1.0] },
-        Vertex { position: [ 1.0, -1.0], uv: [1.0, 1.0] },
-        Vertex { position: [ 1.0,  1.0], uv: [1.0, 0.0] },
-        Vertex { position: [-1.0,  1.0], uv: [0.0, 0.0] },
-    ];
-    let indices: &[u16] = &[0, 1, 2, 2, 3, 0];
-    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Vertex Buffer"),
-        contents: bytemuck::cast_slice(&vertices),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
-    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Index Buffer"),
-        contents: bytemuck::cast_slice(indices),
-        usage: wgpu::BufferUsages::INDEX,
+    // frame around the card
+    let bezel = (args.bezel > 0).then(|| {
+        let texture = args.bezel_texture.as_ref().map(|path| load_image(path, args.ignore_icc).unwrap_or_else(|e| {
+            eprintln!("Failed to load bezel texture {}: {}", path, e);
+            std::process::exit(1);
+        }));
+        // a texture is shown as it is unless it's given a tint
+        let color = args.bezel_color.unwrap_or_else(|| match texture {
+            Some(_) => [1.0; 4],
+            None => bezel::parse_color(DEFAULT_BEZEL_COLOR).unwrap(),
+        });
+        bezel::Bezel::new(&device, &queue, surface_format, color, texture.as_ref())
     });
 
-    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Shader"),
-        source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/shaders.wgsl").into()),
-    });
+    // halo around the card
+    let card_glow = args.card_glow.map(|color| glow::Glow::new(&device, surface_format, color, args.glow_strength));
 
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Pipeline Layout"),
-        bind_group_layouts: &[&bind_group_layout],
-        push_constant_ranges: &[],
-    });
+    // showcase scene, animated off the real clock so it doesn't jump when the loop wraps
+    let mockup = Mockup::new(&device, surface_format);
+    let mut show_mockup = args.mockup;
+    let start_time = Instant::now();
 
-    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Render Pipeline"),
-        layout: Some(&pipeline_layout),
-        vertex: wgpu::VertexState {
-            module: &shader_module,
-            entry_point: Option::from("vs_main"),
-            compilation_options: Default::default(),
-            buffers: &[wgpu::VertexBufferLayout {
-                array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: &[
-                    wgpu::VertexAttribute {
-                        offset: 0,
-                        shader_location: 0,
-                        format: wgpu::VertexFormat::Float32x2,
-                    },
-                    wgpu::VertexAttribute {
-                        offset: 8,
-                        shader_location: 1,
-                        format: wgpu::VertexFormat::Float32x2,
-                    },
-                ],
-            }],
-        },
-        fragment: Some(wgpu::FragmentState {
-            module: &shader_module,
-            entry_point: Option::from("fs_main"),
-            compilation_options: Default::default(),
-            targets: &[Some(wgpu::ColorTargetState {
-                format: surface_format,
-                blend: Some(wgpu::BlendState::REPLACE),
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
-        }),
-        primitive: wgpu::PrimitiveState::default(),
-        depth_stencil: None,
-        multisample: wgpu::MultisampleState::default(),
-        multiview: None,
-        cache: None,
-    });
+    // the render repeated in a grid to check its edges tile, this replaces the mockup, bezel and glow
+    let mut tile = tile::Tile::new(&device, surface_format, img_size, renderer.filter());
+    let mut show_tiled = args.tile_preview;
+
+    // temporal dither drops back to static while paused
+    let mut dither = args.dither;
+
+    // with --mrt-debug the shader also writes a debug output, one of the two outputs is drawn to the window
+    // and the other goes to a scratch target that's never shown
+    let mut debug_scratch = render::ScratchTarget::new(surface_format);
+    let mut show_debug = false;
+
+    // frame rate readout in the corner, drawn straight onto the window so it never ends up in exports
+    let mut text_renderer = text::TextRenderer::new(&device, &queue);
+    let mut show_fps = args.fps_overlay;
+    let mut last_frame = Instant::now();
+    let mut frame_time = 0.0_f32;
+
+    // when the filter was last switched, the new one is shown briefly in the corner
+    let mut filter_switched: Option<Instant> = None;
+    // the last shader error, shown in the window until a reload works
+    let mut shader_error: Option<String> = None;
+
+    // input latency readout, falling back to 60hz if the monitor doesn't say
+    let refresh_rate = window.current_monitor().and_then(|monitor| monitor.refresh_rate_millihertz()).unwrap_or(60_000);
+    let mut latency = latency::LatencyMeter::new(refresh_rate as f32 / 1000.0);
+
+    // the gui draws straight onto the surface after the image
+    #[cfg(feature = "gui")]
+    let mut gui = gui::Gui::new(&window, &device, surface_format);
+
+    // mouse state for drag to pan
+    let mut dragging = false;
+    let mut cursor_pos = winit::dpi::PhysicalPosition::new(0.0, 0.0);
 
     // main loop
     event_loop.run(move |event, event_loop_window_target| {
@@ -357,9 +1360,56 @@ fn main() {
         match event {
             Event::WindowEvent { event, window_id } if window_id == window.id() => {
 
+                // let the gui have first go at input
+                #[cfg(feature = "gui")]
+                if gui.on_window_event(&window, &event) {
+                    return;
+                }
+
                 // receive file change event from watcher
                 if let Ok(msg) = rx.try_recv() {
-                    write_texture(&queue, &texture, &img_path, height, width);
+                    let shader_changed = match (&msg, &shader_path) {
+                        (Ok(change), Some(path)) => change.paths.contains(path),
+                        _ => false,
+                    };
+
+                    if shader_changed {
+                        // a change to the custom shader only matters if it's the one being shown
+                        if using_custom_shader {
+                            let path = shader_path.as_ref().unwrap().to_string_lossy();
+                            let source = load_shader(&path).and_then(|source| check_debug_entry(&args, source));
+                            match source.and_then(|source| renderer.reload_shader(&device, &source).map(|()| source)) {
+                                Ok(source) => {
+                                    renderer.warm_up(&device, &queue, surface_format, args.mrt_debug);
+                                    apply_directives(&args, &mut playback, &source);
+                                    println!("Shader reloaded, press V to compare it with the previous version");
+                                    shader_error = None;
+                                }
+                                Err(e) => {
+                                    eprintln!("Shader error: {}", e);
+                                    shader_error = Some(e);
+                                }
+                            }
+                        }
+                    } else {
+                        // reload whichever image changed, or the one being shown if that can't be told
+                        let index = match &msg {
+                            Ok(change) => image_watch_paths.iter().position(|path| change.paths.contains(path)),
+                            Err(_) => None,
+                        }.unwrap_or(renderer.active_image());
+                        match load_image(&img_paths[index], args.ignore_icc) {
+                            Ok(img) if img.dimensions() != img_size => {
+                                eprintln!("Image size changed, restart to view the new size");
+                            }
+                            Ok(img) => {
+                                renderer.write_image(&queue, index, &img);
+                                image_bounds[index] = opaque_bounds(&img);
+                                bounds = image_bounds[renderer.active_image()];
+                                view_dirty = true;
+                            }
+                            Err(e) => eprintln!("Failed to load image: {}", e),
+                        }
+                    }
                     window.request_redraw();
                     println!("File change received: {:?}", msg);
                 }
@@ -372,13 +1422,351 @@ fn main() {
                         config.width = width;
                         config.height = height;
                         surface.configure(&device, &config);
-                        
+
+                        // keep the placement of the image correct for the new window shape
+                        view_dirty = true;
+
                         window.request_redraw();
                     }
+                    WindowEvent::ModifiersChanged(new_modifiers) => modifiers = new_modifiers.state(),
+                    WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                        // the number row by position, since shift changes which character it types
+                        let slot = match event.physical_key {
+                            PhysicalKey::Code(code) => [
+                                KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5,
+                                KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+                            ].iter().position(|&digit| digit == code),
+                            _ => None,
+                        };
+                        match event.logical_key.as_ref() {
+                            // shift and a number stores the params in that slot
+                            _ if slot.is_some() && modifiers.shift_key() => {
+                                let slot = slot.unwrap();
+                                snapshots.store(slot, &params);
+                                println!("Stored the params in snapshot {}, press C then {} to recall them", slot + 1, slot + 1);
+                            }
+                            // in snapshot mode a number recalls that slot instead of toggling a component
+                            _ if slot.is_some() && snapshot_mode => {
+                                let slot = slot.unwrap();
+                                if snapshots.recall(slot, &mut params) {
+                                    println!("Recalled snapshot {}", slot + 1);
+                                } else {
+                                    println!("Snapshot {} is empty, store one with shift+{}", slot + 1, slot + 1);
+                                }
+                            }
+                            // F toggles framing just the non-transparent artwork
+                            Key::Character("f") => {
+                                view_settings.auto_fit = !view_settings.auto_fit;
+                                if view_settings.auto_fit && bounds.is_none() {
+                                    println!("Auto-fit: image is fully transparent, nothing to frame");
+                                } else {
+                                    println!("Auto-fit: {}", if view_settings.auto_fit { "on" } else { "off" });
+                                }
+                                view_dirty = true;
+                            }
+                            // N toggles showing the image at its native pixel size
+                            Key::Character("n") => {
+                                view_settings.native = !view_settings.native;
+                                println!("Native size: {}", if view_settings.native { "on" } else { "off" });
+                                view_dirty = true;
+                            }
+                            // P toggles whole number scaling only
+                            Key::Character("p") => {
+                                view_settings.integer_scale = !view_settings.integer_scale;
+                                println!("Integer scale: {}", if view_settings.integer_scale { "on" } else { "off" });
+                                view_dirty = true;
+                            }
+                            // 0 resets zoom and pan
+                            Key::Character("0") => {
+                                view_settings.zoom = 1.0;
+                                view_settings.pan = [0.0, 0.0];
+                                view_dirty = true;
+                            }
+                            // Space pauses/resumes the animation
+                            Key::Named(NamedKey::Space) => playback.toggle(),
+                            // I prints everything about the current state, for pasting into bug reports
+                            Key::Character("i") => {
+                                let info = adapter.get_info();
+                                println!("---- state dump ----");
+                                println!("image: {} ({}x{}, {} of {})", img_paths[renderer.active_image()], img_size.0, img_size.1, renderer.active_image() + 1, img_paths.len());
+                                match &args.shader {
+                                    Some(path) if using_custom_shader => println!("shader: {}", path),
+                                    Some(path) => println!("shader: built-in shaders.wgsl (custom {} available)", path),
+                                    None => println!("shader: built-in shaders.wgsl"),
+                                }
+                                println!("params: {:?}", params);
+                                println!("time: {:.3} / {:.3} s at {:.2}x ({})", playback.time, playback.loop_time, playback.speed, if playback.playing { "playing" } else { "paused" });
+                                println!("view: {:?}", view_settings);
+                                println!("pre-blur: {} ({} iterations)", args.pre_blur, args.pre_blur_iterations);
+                                println!("mask: {}", args.mask.as_deref().unwrap_or("none"));
+                                println!("card glow: {}", if card_glow.is_some() { "on" } else { "off" });
+                                println!("filter: {}", filter_name(renderer.filter()));
+                                println!("render scale: {} ({:?}), max dim: {:?}", composite.render_scale, composite.downsample, composite.max_dim);
+                                println!("mockup: {}", show_mockup);
+                                println!("tile preview: {}", show_tiled);
+                                println!("snapshots: {:?} (number keys {})", snapshots.filled(), if snapshot_mode { "recall them" } else { "toggle components" });
+                                println!("automation: {} ({})", args.automation.as_deref().unwrap_or("none"), if recorder.is_some() { "recording" } else { "not recording" });
+                                println!("previous shader: {} ({})", if renderer.has_previous() { "kept" } else { "none" }, if show_previous { "showing" } else { "hidden" });
+                                println!("dither: {:?}", dither);
+                                match &compare {
+                                    Some(compare) => println!("reference: {} ({})", args.reference.as_deref().unwrap_or_default(), if compare.difference { "difference" } else { "side by side" }),
+                                    None => println!("reference: none"),
+                                }
+                                println!("mrt debug: {} (showing {})", args.mrt_debug, if show_debug { "debug output" } else { "final image" });
+                                println!("fps overlay: {} ({:.2} ms/frame)", show_fps, frame_time * 1000.0);
+                                println!("window: {}x{} (scale factor {})", config.width, config.height, window.scale_factor());
+                                println!("surface: {:?}, {:?}, {:?}", config.format, config.present_mode, config.alpha_mode);
+                                println!("adapter: {} ({:?}, {:?})", info.name, info.backend, info.device_type);
+                                println!("driver: {} {}", info.driver, info.driver_info);
+                                println!("version: {}", env!("CARGO_PKG_VERSION"));
+                                println!("--------------------");
+                            }
+                            // [ and ] adjust the CRT curvature
+                            Key::Character("[") | Key::Character("]") => {
+                                let step = if event.logical_key == Key::Character("[".into()) { -0.05 } else { 0.05 };
+                                params.crt_curvature = (params.crt_curvature + step).clamp(0.0, 1.0);
+                                println!("CRT curvature: {:.2}", params.crt_curvature);
+                            }
+                            // 1-5 switch individual parts of the effect on and off
+                            Key::Character(digit @ ("1" | "2" | "3" | "4" | "5")) => {
+                                let index = digit.parse::<usize>().unwrap() - 1;
+                                params.toggle_component(index);
+                                let (_, name) = render::COMPONENTS[index];
+                                println!(
+                                    "{}: {} (mask {:05b}, on: {})",
+                                    name,
+                                    if params.component_mask & render::COMPONENTS[index].0 != 0 { "on" } else { "off" },
+                                    params.component_mask,
+                                    params.active_components().join(", "),
+                                );
+                            }
+                            // , and . adjust the chromatic aberration
+                            Key::Character(",") | Key::Character(".") => {
+                                let step = if event.logical_key == Key::Character(",".into()) { -0.25 } else { 0.25 };
+                                params.chromatic_aberration = (params.chromatic_aberration + step).clamp(0.0, 4.0);
+                                println!("Chromatic aberration: {:.2}", params.chromatic_aberration);
+                            }
+                            // - and = fade the whole card out and back in
+                            Key::Character("-") | Key::Character("=") => {
+                                let step = if event.logical_key == Key::Character("-".into()) { -0.1 } else { 0.1 };
+                                params.global_opacity = (params.global_opacity + step).clamp(0.0, 1.0);
+                                println!("Opacity: {:.1}", params.global_opacity);
+                            }
+                            // M toggles the mockup scene
+                            Key::Character("m") => {
+                                show_mockup = !show_mockup;
+                                println!("Mockup: {}", if show_mockup { "on" } else { "off" });
+                            }
+                            // E saves the current params as wgsl consts and as toml for --params
+                            Key::Character("e") => {
+                                let effect = match &args.shader {
+                                    Some(path) if using_custom_shader => path.as_str(),
+                                    _ => BUILTIN_EFFECT,
+                                };
+                                let stem = Path::new(&img_paths[renderer.active_image()]).file_stem()
+                                    .map_or(String::from("image"), |stem| stem.to_string_lossy().to_string());
+                                let wgsl_path = format!("{}-params.wgsl", stem);
+                                let toml_path = format!("{}-params.toml", stem);
+                                let saved = std::fs::write(&wgsl_path, preset::to_wgsl(&params, effect))
+                                    .and_then(|()| std::fs::write(&toml_path, preset::to_toml(&params, effect)));
+                                match saved {
+                                    Ok(()) => println!("Saved params to {} and {}", wgsl_path, toml_path),
+                                    Err(e) => eprintln!("Failed to save params: {}", e),
+                                }
+                            }
+                            // B cycles through no dither, static dither and temporal dither
+                            Key::Character("b") => {
+                                dither = dither.next();
+                                println!("Dither: {:?}", dither);
+                            }
+                            // K starts recording changes to the params as automation, and stops and saves it
+                            Key::Character("k") => match recorder.take() {
+                                Some(recording) => save_recording(recording, &img_paths[renderer.active_image()]),
+                                None => {
+                                    recorder = Some(automation::Recorder::default());
+                                    println!("Recording automation, change the params then press K again (it stops by itself after one loop)");
+                                }
+                            },
+                            // C switches the number keys between toggling components and recalling snapshots
+                            Key::Character("c") => {
+                                snapshot_mode = !snapshot_mode;
+                                if snapshot_mode {
+                                    println!("Number keys recall snapshots (stored: {:?})", snapshots.filled());
+                                } else {
+                                    println!("Number keys toggle effect components");
+                                }
+                            }
+                            // R switches between the single image and a tiled grid of it
+                            Key::Character("r") => {
+                                show_tiled = !show_tiled;
+                                println!("Tile preview: {}", if show_tiled { "on" } else { "off" });
+                            }
+                            // D switches between the custom shader and the built-in one, rereading the custom one each time
+                            Key::Character("d") => {
+                                let Some(path) = &args.shader else {
+                                    println!("No custom shader to compare against, pass one with --shader");
+                                    return;
+                                };
+                                let source = if using_custom_shader { Ok(SHADER_SOURCE.to_string()) } else { load_shader(path) };
+                                let source = source.and_then(|source| check_debug_entry(&args, source));
+                                match source.and_then(|source| renderer.set_shader(&device, &source).map(|()| source)) {
+                                    Ok(source) => {
+                                        renderer.warm_up(&device, &queue, surface_format, args.mrt_debug);
+                                        apply_directives(&args, &mut playback, &source);
+                                        using_custom_shader = !using_custom_shader;
+                                        println!("Shader: {}", if using_custom_shader { path.as_str() } else { "built-in" });
+                                        shader_error = None;
+                                    }
+                                    Err(e) => {
+                                        eprintln!("Shader error: {}", e);
+                                        shader_error = Some(e);
+                                    }
+                                }
+                            }
+                            // G switches between the final image and the shader's debug output
+                            Key::Character("g") if args.mrt_debug => {
+                                show_debug = !show_debug;
+                                println!("Showing: {}", if show_debug { "debug output" } else { "final image" });
+                            }
+                            // L highlights where the scanlines fall against the texel rows
+                            Key::Character("l") => {
+                                params.scanline_overlay ^= 1;
+                                println!("Scanline overlay: {}", if params.scanline_overlay != 0 { "on" } else { "off" });
+                            }
+                            // O toggles the fps overlay
+                            Key::Character("o") => {
+                                show_fps = !show_fps;
+                                println!("FPS overlay: {}", if show_fps { "on" } else { "off" });
+                            }
+                            // X switches the reference (or previous shader) comparison between side by side and difference
+                            Key::Character("x") => {
+                                let compare = if show_previous { Some(&mut previous_compare) } else { compare.as_mut() };
+                                let Some(compare) = compare else {
+                                    println!("No reference image to compare against, pass one with --reference");
+                                    return;
+                                };
+                                compare.difference = !compare.difference;
+                                println!("Comparison: {}", if compare.difference { "difference" } else { "side by side" });
+                            }
+                            // V shows the custom shader next to its version from before the last reload
+                            Key::Character("v") => {
+                                if !renderer.has_previous() {
+                                    println!("No previous shader yet, it's kept each time the custom shader reloads");
+                                    return;
+                                }
+                                show_previous = !show_previous;
+                                println!("Previous shader: {}", if show_previous { "on (current on the left, previous on the right)" } else { "off" });
+                            }
+                            // S switches between linear and nearest filtering
+                            Key::Character("s") => {
+                                let filter = match renderer.filter() {
+                                    wgpu::FilterMode::Linear => wgpu::FilterMode::Nearest,
+                                    wgpu::FilterMode::Nearest => wgpu::FilterMode::Linear,
+                                };
+                                // the comparison and tiled previews resample the render, so they follow along
+                                renderer.set_filter(&device, filter);
+                                if let Some(compare) = compare.as_mut() {
+                                    compare.set_filter(&device, filter);
+                                }
+                                previous_compare.set_filter(&device, filter);
+                                tile.set_filter(&device, filter);
+                                filter_switched = Some(Instant::now());
+                                println!("Filter: {}", filter_name(filter));
+                            }
+                            // Tab moves on to the next image given on the command line
+                            Key::Named(NamedKey::Tab) if img_paths.len() > 1 => {
+                                let index = (renderer.active_image() + 1) % renderer.image_count();
+                                renderer.select_image(index);
+                                bounds = image_bounds[index];
+                                view_dirty = true;
+                                println!("Image {}/{}: {}", index + 1, img_paths.len(), img_paths[index]);
+                            }
+                            // T switches between smoothed and raw frame timing
+                            Key::Character("t") => playback.toggle_smoothing(),
+                            // Left/Right step through the loop
+                            Key::Named(NamedKey::ArrowLeft) => playback.seek(playback.progress() - 0.05),
+                            Key::Named(NamedKey::ArrowRight) => playback.seek(playback.progress() + 0.05),
+                            _ => {}
+                        }
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        // scroll to zoom
+                        let steps = match delta {
+                            MouseScrollDelta::LineDelta(_, y) => y,
+                            MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 50.0,
+                        };
+                        view_settings.zoom_by(steps);
+                        view_dirty = true;
+                    }
+                    WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                        dragging = state == ElementState::Pressed;
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
+                        latency.input();
+
+                        // drag with the left mouse button to pan
+                        if dragging {
+                            view_settings.pan[0] += (position.x - cursor_pos.x) as f32;
+                            view_settings.pan[1] += (position.y - cursor_pos.y) as f32;
+                            view_dirty = true;
+                        }
+                        cursor_pos = position;
+                    }
                     WindowEvent::CloseRequested => {
+                        match snapshots.save(&snapshot_path) {
+                            Ok(true) => println!("Saved snapshots to {}", snapshot_path),
+                            Ok(false) => {}
+                            Err(e) => eprintln!("Failed to save snapshots: {}", e),
+                        }
                         event_loop_window_target.exit();
                     }
                     WindowEvent::RedrawRequested => {
+                        latency.frame_started();
+
+                        // keep a smoothed frame time so the overlay is readable
+                        let now = Instant::now();
+                        let delta = now.duration_since(last_frame).as_secs_f32();
+                        last_frame = now;
+                        frame_time = if frame_time == 0.0 { delta } else { frame_time * 0.9 + delta * 0.1 };
+
+                        // move the animation on
+                        playback.tick();
+                        #[cfg(feature = "osc-clock")]
+                        if let Some(time) = external_clock.as_mut().and_then(|clock| clock.poll()) {
+                            playback.time = time.rem_euclid(playback.loop_time);
+                        }
+                        // a recording stops by itself when the loop comes back round, so it covers one pass at most
+                        if recorder.as_ref().and_then(|recorder| recorder.last_time()).is_some_and(|last| playback.time < last) {
+                            save_recording(recorder.take().unwrap(), &img_paths[renderer.active_image()]);
+                        }
+                        params.time = playback.time;
+                        if let (Some(automation), None) = (&automation, &recorder) {
+                            automation.apply(playback.time, &mut params);
+                        }
+                        params.frame = params.frame.wrapping_add(1);
+                        // a paused frame would crawl with temporal dither, so it holds still until playing again
+                        let frame_dither = if dither == Dither::Temporal && !playback.playing { Dither::Static } else { dither };
+                        params.dither = frame_dither as u32;
+                        renderer.write_params(&queue, &params);
+
+                        // update where the image sits if anything changed since the last frame
+                        if view_dirty {
+                            current_view = view::compute_view(img_size, (config.width, config.height), bounds, &mut view_settings);
+                            view_dirty = false;
+                        }
+                        let mut frame_view = current_view;
+                        let elapsed = start_time.elapsed().as_secs_f32();
+                        if show_mockup {
+                            Mockup::animate_view(&mut frame_view, elapsed);
+                        }
+                        // when comparing or tiling, the image is drawn whole into its own texture and placed from there.
+                        // comparing wins if both are on
+                        let comparing_previous = show_previous && renderer.has_previous();
+                        let active_compare = if comparing_previous { Some(&previous_compare) } else { compare.as_ref() };
+                        let tiling = show_tiled && active_compare.is_none();
+                        let offscreen = active_compare.is_some() || tiling;
+                        renderer.write_view(&queue, if offscreen { &render::FULL_VIEW } else { &frame_view });
+
                         // Get the current surface texture
                         let frame = surface
                             .get_current_texture()
@@ -394,39 +1782,130 @@ fn main() {
                             }
                         );
                         
-                        // Begin render pass
-                        {
-                            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                                label: Some("Render Pass"),
-                                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                                    view: &view,
-                                    depth_slice: None,
-                                    resolve_target: None,
-                                    ops: wgpu::Operations {
-                                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                                            r: 0.1,
-                                            g: 0.2,
-                                            b: 0.3,
-                                            a: 1.0,
-                                        }),
-                                        store: wgpu::StoreOp::Store,
-                                    },
-                                })],
-                                depth_stencil_attachment: None,
-                                timestamp_writes: None,
-                                occlusion_query_set: None,
-                            });
-                            
-                            render_pass.set_pipeline(&render_pipeline);
-                            render_pass.set_bind_group(0, &bind_group, &[]);
-                            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                            render_pass.draw_indexed(0..6, 0, 0..1);
+                        // the scene goes straight to the window unless it's being rendered at a different scale
+                        let window_size = (config.width, config.height);
+                        let scene_target = if composite.enabled(window_size) {
+                            composite.target_view(&device, window_size).clone()
+                        } else {
+                            view.clone()
+                        };
+
+                        // the image goes to the comparison's (or tiling's) texture instead of the scene
+                        let scene_size = if composite.enabled(window_size) { composite.scaled_size(window_size) } else { window_size };
+                        let (live_target, live_size) = match active_compare {
+                            Some(compare) => (compare.live_view(), img_size),
+                            None if tiling => (tile.live_view(), img_size),
+                            None => (&scene_target, scene_size),
+                        };
+
+                        // swap which output lands in the scene when looking at the debug output
+                        let scratch = args.mrt_debug.then(|| debug_scratch.view(&device, live_size));
+                        let (image_target, debug_target) = match scratch {
+                            Some(scratch) if show_debug => (scratch, Some(live_target)),
+                            Some(scratch) => (live_target, Some(scratch)),
+                            None => (live_target, None),
+                        };
+
+                        let background = if see_through { wgpu::Color::TRANSPARENT } else { wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 } };
+                        if offscreen {
+                            renderer.draw(&device, &mut encoder, image_target, surface_format, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), debug_target);
+                            if comparing_previous {
+                                renderer.draw_previous(&device, &mut encoder, previous_compare.reference_view(), surface_format);
+                            }
+
+                            // the comparison and tiling only draw their quads, so clear the scene to the background first
+                            clear(&mut encoder, &scene_target, background);
+                            match active_compare {
+                                Some(compare) => compare.draw(&queue, &mut encoder, &scene_target, scene_size, &mut view_settings, bounds),
+                                None => tile.draw(&queue, &mut encoder, &scene_target, scene_size, &mut view_settings),
+                            }
+                        } else {
+                            // composite the image over the background or the mockup scene, blending rather than
+                            // replacing so its transparency (and --opacity) shows
+                            if show_mockup {
+                                let aspect = config.width as f32 / config.height as f32;
+                                mockup.draw(&queue, &mut encoder, &scene_target, elapsed, aspect);
+                            } else {
+                                clear(&mut encoder, &scene_target, background);
+                            }
+                            let load = wgpu::LoadOp::Load;
+
+                            // the glow goes around the outside of the card and its bezel
+                            if let Some(card_glow) = &card_glow {
+                                let glow_view = view::grow_view(&frame_view, img_size, view_settings.bezel + glow::GLOW_EXTENT);
+                                let silhouette = glow::Silhouette {
+                                    size: [
+                                        frame_view.uv_scale[0] * img_size.0 as f32 + 2.0 * view_settings.bezel as f32,
+                                        frame_view.uv_scale[1] * img_size.1 as f32 + 2.0 * view_settings.bezel as f32,
+                                    ],
+                                    radius: params.corner_radius,
+                                };
+                                card_glow.draw(&queue, &mut encoder, &scene_target, &glow_view, &silhouette, load);
+                            }
+
+                            // the bezel goes underneath so the card's transparent corners show it
+                            if let Some(bezel) = &bezel {
+                                let bezel_view = view::grow_view(&frame_view, img_size, view_settings.bezel);
+                                bezel.draw(&queue, &mut encoder, &scene_target, &bezel_view, load);
+                            }
+                            renderer.draw(&device, &mut encoder, image_target, surface_format, load, debug_target);
+                        }
+
+                        if composite.enabled(window_size) {
+                            composite.draw(&queue, &mut encoder, &view, window_size);
+                        }
+
+                        if show_fps {
+                            let lines = [format!("{:.0} fps", 1.0 / frame_time), format!("{:.2} ms", frame_time * 1000.0)];
+                            let block = text::TextBlock { lines: &lines, corner: text::Corner::TopRight, scale: pixel_scale as f32, color: [1.0, 1.0, 1.0, 1.0] };
+                            text_renderer.draw(&device, &mut encoder, &view, surface_format, window_size, &block);
+                        }
+
+                        if args.latency_debug {
+                            let lines = latency.lines(config.desired_maximum_frame_latency);
+                            let block = text::TextBlock { lines: &lines, corner: text::Corner::TopLeft, scale: pixel_scale as f32, color: [1.0, 1.0, 0.4, 1.0] };
+                            text_renderer.draw(&device, &mut encoder, &view, surface_format, window_size, &block);
+                        }
+
+                        #[cfg(feature = "gui")]
+                        let effect_name = match &args.shader {
+                            Some(path) if using_custom_shader => path.as_str(),
+                            _ => BUILTIN_EFFECT,
+                        };
+                        #[cfg(feature = "gui")]
+                        gui.draw(&window, &device, &queue, &mut encoder, &view, |ctx| {
+                            gui::playback_panel(ctx, &mut playback);
+                            gui::params_window(ctx, &mut params, effect_name);
+                        });
+
+                        // record after the gui so this frame's slider changes are in
+                        if let Some(recorder) = &mut recorder {
+                            recorder.record(playback.time, &params);
+                        }
+
+                        // the filter notice goes over the gui so the playback bar can't hide it
+                        if let Some(switched) = filter_switched {
+                            if switched.elapsed().as_secs_f32() < FILTER_NOTICE_SECS {
+                                let lines = [format!("filter: {}", filter_name(renderer.filter()))];
+                                let block = text::TextBlock { lines: &lines, corner: text::Corner::BottomLeft, scale: 2.0 * pixel_scale as f32, color: [1.0, 1.0, 1.0, 1.0] };
+                                text_renderer.draw(&device, &mut encoder, &view, surface_format, window_size, &block);
+                            } else {
+                                filter_switched = None;
+                            }
+                        }
+
+                        // the last good shader keeps running, so say why edits aren't showing up
+                        if let Some(error) = &shader_error {
+                            let scale = pixel_scale as f32;
+                            let lines = text::wrap(&format!("shader error: {}", error), window_size.0, scale, SHADER_ERROR_LINES);
+                            let block = text::TextBlock { lines: &lines, corner: text::Corner::BottomRight, scale, color: [1.0, 0.45, 0.4, 1.0] };
+                            text_renderer.draw(&device, &mut encoder, &view, surface_format, window_size, &block);
                         }
                         
                         // Submit command buffer
                         queue.submit(std::iter::once(encoder.finish()));
                         frame.present();
+                        latency.presented();
                     }
                     _ => {}
                 }
@@ -438,4 +1917,27 @@ fn main() {
             _ => {}
         }
     }).expect("Event loop error");
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opaque_bounds_frames_visible_pixels() {
+        let mut img = RgbaImage::new(10, 8);
+        img.put_pixel(2, 3, image::Rgba([0, 0, 0, 1]));
+        img.put_pixel(6, 5, image::Rgba([255, 255, 255, 255]));
+        // colour with no alpha doesn't count
+        img.put_pixel(9, 7, image::Rgba([255, 0, 0, 0]));
+        assert_eq!(opaque_bounds(&img), Some((2, 3, 5, 3)));
+
+        img.put_pixel(0, 0, image::Rgba([0, 0, 0, 255]));
+        assert_eq!(opaque_bounds(&img), Some((0, 0, 7, 6)));
+    }
+
+    #[test]
+    fn opaque_bounds_of_nothing() {
+        assert_eq!(opaque_bounds(&RgbaImage::new(4, 4)), None);
+        assert_eq!(opaque_bounds(&RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 9]))), Some((0, 0, 1, 1)));
+    }
+}
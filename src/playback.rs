@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+// the flicker in the default shader repeats every 2π seconds, so that makes a seamless default loop
+pub const DEFAULT_LOOP_TIME: f32 = std::f32::consts::TAU;
+
+// how many recent frame times are averaged when smoothing
+const SMOOTHING_FRAMES: usize = 8;
+
+// keeps track of the animation clock so it can be paused, looped and seeked
+pub struct Playback {
+    pub time: f32,
+    pub loop_time: f32,
+    pub playing: bool,
+    // how fast the clock runs compared to real time
+    pub speed: f32,
+    // advance by the average of recent frame times rather than the exact time since the last frame
+    pub smoothing: bool,
+    last_tick: Instant,
+    recent_deltas: VecDeque<f32>,
+}
+
+impl Playback {
+    pub fn new(loop_time: f32, smoothing: bool) -> Self {
+        Self {
+            time: 0.0,
+            loop_time,
+            playing: true,
+            speed: 1.0,
+            smoothing,
+            last_tick: Instant::now(),
+            recent_deltas: VecDeque::with_capacity(SMOOTHING_FRAMES),
+        }
+    }
+
+    // advance the clock by however long it's been since the last tick, wrapping at the loop period
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let raw_delta = now.duration_since(self.last_tick).as_secs_f32();
+        self.last_tick = now;
+
+        // keep a short history so uneven frame delivery doesn't show up as uneven motion
+        if self.recent_deltas.len() == SMOOTHING_FRAMES {
+            self.recent_deltas.pop_front();
+        }
+        self.recent_deltas.push_back(raw_delta);
+
+        let delta = if self.smoothing {
+            self.recent_deltas.iter().sum::<f32>() / self.recent_deltas.len() as f32
+        } else {
+            raw_delta
+        };
+
+        if self.playing {
+            self.time = (self.time + delta * self.speed) % self.loop_time;
+        }
+    }
+
+    // how far through the loop we are, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        self.time / self.loop_time
+    }
+
+    // jump to a point in the loop, from 0 to 1
+    pub fn seek(&mut self, progress: f32) {
+        self.time = progress.clamp(0.0, 1.0) * self.loop_time % self.loop_time;
+    }
+
+    pub fn toggle_smoothing(&mut self) {
+        self.smoothing = !self.smoothing;
+        println!("Frame timing: {}", if self.smoothing { "smoothed" } else { "raw" });
+    }
+
+    pub fn toggle(&mut self) {
+        self.playing = !self.playing;
+        println!("Playback: {}", if self.playing { "playing" } else { "paused" });
+    }
+}
@@ -0,0 +1,48 @@
+// how many unused textures are kept, the oldest is dropped to make room
+const MAX_FREE: usize = 8;
+
+// offscreen textures kept after they stop being used, so coming back to a size (like dragging a window edge
+// back and forth) reuses one instead of allocating again
+#[derive(Default)]
+pub struct TexturePool {
+    // oldest first
+    free: Vec<wgpu::Texture>,
+}
+
+impl TexturePool {
+    // a texture with this format, size and usage, reused from the pool if there is one
+    pub fn take(
+        &mut self,
+        device: &wgpu::Device,
+        label: &str,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        usage: wgpu::TextureUsages,
+    ) -> wgpu::Texture {
+        let matches = |texture: &wgpu::Texture| {
+            texture.format() == format && texture.width() == size.0 && texture.height() == size.1 && texture.usage() == usage
+        };
+        if let Some(index) = self.free.iter().rposition(matches) {
+            return self.free.remove(index);
+        }
+
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: Default::default(),
+        })
+    }
+
+    // hand back a texture that isn't needed any more
+    pub fn give(&mut self, texture: wgpu::Texture) {
+        if self.free.len() == MAX_FREE {
+            self.free.remove(0);
+        }
+        self.free.push(texture);
+    }
+}
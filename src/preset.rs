@@ -0,0 +1,90 @@
+use crate::render::{Params, SWEEPABLE};
+
+// the numeric params worth saving, time moves on by itself so it's left out
+pub fn tuned_values(params: &Params) -> impl Iterator<Item = (&'static str, f32)> + '_ {
+    SWEEPABLE
+        .iter()
+        .filter(|&&(name, _)| name != "time")
+        .map(|&(name, _)| (name, params.value(name).expect("SWEEPABLE names are all params")))
+}
+
+// the params as wgsl consts, ready to paste into a shader to bake the look in
+pub fn to_wgsl(params: &Params, effect: &str) -> String {
+    let mut out = format!("// tuned with {}\n", effect);
+    for (name, value) in tuned_values(params) {
+        out += &format!("const {}: f32 = {:?};\n", name.to_uppercase(), value);
+    }
+    out += &format!("const COMPONENT_MASK: u32 = {}u;\n", params.component_mask);
+    out
+}
+
+// the params as toml, which `--params` can load back in
+pub fn to_toml(params: &Params, effect: &str) -> String {
+    let mut out = format!("effect = \"{}\"\n\n[params]\n", effect.replace('\\', "\\\\").replace('"', "\\\""));
+    for (name, value) in tuned_values(params) {
+        out += &format!("{} = {:?}\n", name, value);
+    }
+    out += &format!("component_mask = {}\n", params.component_mask);
+    out
+}
+
+// read params saved by `to_toml` over the top of `params`, anything the file doesn't mention is left alone
+pub fn parse_toml(source: &str, params: &mut Params) -> Result<(), String> {
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected key = value", number + 1));
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        // the effect is only a note of what the values were tuned with
+        if key == "effect" {
+            continue;
+        }
+        if key == "component_mask" {
+            params.component_mask = value.parse().map_err(|_| format!("line {}: invalid component_mask {}", number + 1, value))?;
+            continue;
+        }
+        let Some(slot) = params.value_mut(key) else {
+            return Err(format!("line {}: unknown param {}", number + 1, key));
+        };
+        *slot = value.parse().map_err(|_| format!("line {}: invalid value for {}: {}", number + 1, key, value))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let params = Params { bloom_fac: 0.75, chromatic_aberration: 2.5, global_opacity: 0.5, component_mask: 3, ..Params::default() };
+        let mut parsed = Params::default();
+        parse_toml(&to_toml(&params, "C:\\shaders\\\"foil\".wgsl"), &mut parsed).unwrap();
+        assert_eq!(tuned_values(&parsed).collect::<Vec<_>>(), tuned_values(&params).collect::<Vec<_>>());
+        assert_eq!(parsed.component_mask, 3);
+    }
+
+    #[test]
+    fn leaves_unmentioned_params_alone() {
+        let mut params = Params { crt_curvature: 0.3, ..Params::default() };
+        parse_toml("# a comment\n\n[params]\nbloom_fac = 2\n", &mut params).unwrap();
+        assert_eq!((params.bloom_fac, params.crt_curvature), (2.0, 0.3));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for (source, error) in [
+            ("bloom_fac 2.0", "line 1: expected key = value"),
+            ("[params]\nnot_a_param = 1", "line 2: unknown param not_a_param"),
+            ("bloom_fac = lots", "line 1: invalid value for bloom_fac: lots"),
+            ("component_mask = 1.5", "line 1: invalid component_mask 1.5"),
+        ] {
+            assert_eq!(parse_toml(source, &mut Params::default()), Err(String::from(error)));
+        }
+    }
+}
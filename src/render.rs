@@ -0,0 +1,832 @@
+use std::collections::HashMap;
+use image::RgbaImage;
+use wgpu::util::DeviceExt;
+use crate::blur::Blur;
+use crate::pool::TexturePool;
+use crate::shader_check;
+use crate::view::View;
+
+// effect parameters passed to the fragment shader, must match `Params` in the shader
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Params {
+    pub time: f32,
+    pub artifact_amplifier: f32,
+    pub crt_amount_adjusted: f32,
+    pub bloom_fac: f32,
+    pub crt_curvature: f32,
+    // how far apart the red and blue channels are pulled, in texels
+    pub chromatic_aberration: f32,
+    // which parts of the effect are switched on, see `COMPONENTS`
+    pub component_mask: u32,
+    // how much the --mask image limits the effect, 0 ignores it and 1 follows it exactly
+    pub mask_strength: f32,
+    // round off the corners of the card by this many image pixels, 0 leaves them square
+    pub corner_radius: f32,
+    // shift the scanlines along by this fraction of their spacing, to line them up with the game's
+    pub scanline_offset: f32,
+    // tint the scanlines and outline the texel rows so their alignment can be checked, 0 or 1
+    pub scanline_overlay: u32,
+    // counts up once per frame drawn, for anything that changes every frame rather than over time
+    pub frame: u32,
+    // a `Dither` as a number, to hide banding in gradients
+    pub dither: u32,
+    // multiplies the alpha of everything drawn, 0 to 1
+    pub global_opacity: f32,
+    // shade a test pattern made from the texture coordinates instead of the image, 0 or 1
+    pub uv_test: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            artifact_amplifier: 1.0,
+            crt_amount_adjusted: 1.0,
+            bloom_fac: 1.0,
+            // the original look has no curvature at all
+            crt_curvature: 0.0,
+            chromatic_aberration: 0.0,
+            component_mask: ALL_COMPONENTS,
+            mask_strength: 1.0,
+            corner_radius: 0.0,
+            scanline_offset: 0.0,
+            scanline_overlay: 0,
+            frame: 0,
+            dither: Dither::Off as u32,
+            global_opacity: 1.0,
+            uv_test: 0,
+        }
+    }
+}
+
+// noise added to the output to break up banding, must match the DITHER_ constants in the shader
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Dither {
+    Off = 0,
+    // the same pattern every frame
+    Static = 1,
+    // a new pattern every frame, smoother in motion but noisy in stills
+    Temporal = 2,
+}
+
+impl std::str::FromStr for Dither {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "static" => Ok(Self::Static),
+            "temporal" => Ok(Self::Temporal),
+            _ => Err(format!("unknown dither {}, expected off, static or temporal", s)),
+        }
+    }
+}
+
+impl Dither {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Static,
+            Self::Static => Self::Temporal,
+            Self::Temporal => Self::Off,
+        }
+    }
+}
+
+// the parts of the effect that can be switched off on their own, as bits of `Params::component_mask`.
+// these must match the COMPONENT_ constants in the shader
+pub const COMPONENTS: [(u32, &str); 5] = [
+    (1 << 0, "flicker"),
+    (1 << 1, "colour bias"),
+    (1 << 2, "bloom contrast"),
+    (1 << 3, "curvature"),
+    (1 << 4, "chromatic aberration"),
+];
+pub const ALL_COMPONENTS: u32 = (1 << COMPONENTS.len()) - 1;
+
+// the params `--sweep` can step through, by their name in the params json and a label short enough to fit on a card
+pub const SWEEPABLE: [(&str, &str); 10] = [
+    ("time", "t"),
+    ("artifact_amplifier", "amp"),
+    ("crt_amount_adjusted", "crt"),
+    ("bloom_fac", "bloom"),
+    ("crt_curvature", "curve"),
+    ("chromatic_aberration", "ca"),
+    ("mask_strength", "mask"),
+    ("corner_radius", "radius"),
+    ("scanline_offset", "scan"),
+    ("global_opacity", "alpha"),
+];
+
+impl Params {
+    // flip one component on or off by its index in `COMPONENTS`
+    pub fn toggle_component(&mut self, index: usize) {
+        self.component_mask ^= COMPONENTS[index].0;
+    }
+
+    // the components that are switched on, by name
+    pub fn active_components(&self) -> Vec<&'static str> {
+        COMPONENTS.iter().filter(|(bit, _)| self.component_mask & bit != 0).map(|&(_, name)| name).collect()
+    }
+
+    pub fn value(&self, name: &str) -> Option<f32> {
+        let mut params = *self;
+        params.value_mut(name).map(|value| *value)
+    }
+
+    // one of the numeric params by its name in `SWEEPABLE`
+    pub fn value_mut(&mut self, name: &str) -> Option<&mut f32> {
+        match name {
+            "time" => Some(&mut self.time),
+            "artifact_amplifier" => Some(&mut self.artifact_amplifier),
+            "crt_amount_adjusted" => Some(&mut self.crt_amount_adjusted),
+            "bloom_fac" => Some(&mut self.bloom_fac),
+            "crt_curvature" => Some(&mut self.crt_curvature),
+            "chromatic_aberration" => Some(&mut self.chromatic_aberration),
+            "mask_strength" => Some(&mut self.mask_strength),
+            "corner_radius" => Some(&mut self.corner_radius),
+            "scanline_offset" => Some(&mut self.scanline_offset),
+            "global_opacity" => Some(&mut self.global_opacity),
+            _ => None,
+        }
+    }
+}
+
+// the view that stretches the whole image over the whole target
+pub const FULL_VIEW: View = View {
+    quad_scale: [1.0, 1.0],
+    quad_offset: [0.0, 0.0],
+    uv_offset: [0.0, 0.0],
+    uv_scale: [1.0, 1.0],
+    tilt: [0.0, 1.0],
+};
+
+// vertex data for a quad
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
+const VERTICES: [Vertex; 4] = [
+    Vertex { position: [-1.0, -1.0], uv: [0.0, 1.0] },
+    Vertex { position: [ 1.0, -1.0], uv: [1.0, 1.0] },
+    Vertex { position: [ 1.0,  1.0], uv: [1.0, 0.0] },
+    Vertex { position: [-1.0,  1.0], uv: [0.0, 0.0] },
+];
+const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+
+// check the shader is valid and matches our uniforms before we build anything with it
+fn create_shader_module(device: &wgpu::Device, shader_source: &str) -> Result<wgpu::ShaderModule, String> {
+    shader_check::validate(shader_source)?;
+
+    // make sure the shader agrees with us on the size of each uniform
+    let uniforms = [
+        (2, "Params", std::mem::size_of::<Params>()),
+        (3, "View", std::mem::size_of::<View>()),
+    ];
+    shader_check::check_uniform_sizes(shader_source, &uniforms)?;
+
+    Ok(device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader"),
+        source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+    }))
+}
+
+fn create_sampler(device: &wgpu::Device, filter: wgpu::FilterMode) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("image_sampler"),
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        mag_filter: filter,
+        min_filter: filter,
+        ..Default::default()
+    })
+}
+
+// a small texture holding one colour
+fn create_solid_texture(device: &wgpu::Device, queue: &wgpu::Queue, label: &str, color: [u8; 4]) -> wgpu::Texture {
+    device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: Default::default(),
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &color,
+    )
+}
+
+// copy `img` over the whole of `texture`. gl (and angle, which sits on top of it) has garbled images whose rows
+// aren't a multiple of `COPY_BYTES_PER_ROW_ALIGNMENT` bytes for some users, so there the rows are padded out
+// to that first. other backends take the rows as they are
+fn upload_rgba(queue: &wgpu::Queue, texture: &wgpu::Texture, img: &RgbaImage, backend: wgpu::Backend) {
+    let (width, height) = img.dimensions();
+    let row = 4 * width;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded;
+    let (data, bytes_per_row) = if backend == wgpu::Backend::Gl && row % align != 0 {
+        let padded_row = row.div_ceil(align) * align;
+        let mut rows = vec![0u8; (padded_row * height) as usize];
+        for (src, dst) in img.as_raw().chunks_exact(row as usize).zip(rows.chunks_exact_mut(padded_row as usize)) {
+            dst[..row as usize].copy_from_slice(src);
+        }
+        padded = rows;
+        (padded.as_slice(), padded_row)
+    } else {
+        (img.as_raw().as_slice(), row)
+    };
+
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        data,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_row),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+}
+
+// a texture for an image to be drawn, and a view of it
+fn create_image_texture(device: &wgpu::Device, size: (u32, u32)) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        label: Some("image_texture"),
+        view_formats: Default::default(),
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    (texture, view)
+}
+
+// the textures and sampler the shader reads besides the image, kept together so the bind groups can be
+// rebuilt when one changes
+struct Textures {
+    sampler: wgpu::Sampler,
+    // blurred copy of the image for shaders that want one, redone whenever the image or radius changes
+    blurred: wgpu::TextureView,
+    // where the effect applies, white everywhere unless there's a --mask
+    mask: wgpu::TextureView,
+}
+
+// tie the textures, sampler and buffers to the layout's bindings
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    image: &wgpu::TextureView,
+    textures: &Textures,
+    params_buffer: &wgpu::Buffer,
+    view_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("texture_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(image),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&textures.sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: params_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: view_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::TextureView(&textures.blurred),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(&textures.mask),
+            },
+        ],
+    })
+}
+
+// one pipeline per target format, blend mode and debug output, created the first time we draw with them
+type Pipelines = HashMap<(wgpu::TextureFormat, bool, bool), wgpu::RenderPipeline>;
+
+// everything needed to draw the shaded image, shared by the window and offscreen exports
+pub struct Renderer {
+    // every image that can be shown, all the same size, and which one is being drawn
+    images: Vec<(wgpu::Texture, wgpu::TextureView)>,
+    active_image: usize,
+    textures: Textures,
+    blur: Blur,
+    pre_blur: f32,
+    blur_dirty: bool,
+
+    params_buffer: wgpu::Buffer,
+    view_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    // one per image
+    bind_groups: Vec<wgpu::BindGroup>,
+    // how the image is filtered when it's scaled
+    filter: wgpu::FilterMode,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    shader_module: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+
+    pipelines: Pipelines,
+
+    // the shader from before the last reload and its pipelines, kept for comparing against
+    previous: Option<(wgpu::ShaderModule, Pipelines)>,
+
+    // which graphics api the device is on, uploads need extra care on gl
+    backend: wgpu::Backend,
+}
+
+impl Renderer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        backend: wgpu::Backend,
+        img: &RgbaImage,
+        shader_source: &str,
+    ) -> Result<Self, String> {
+        let shader_module = create_shader_module(device, shader_source)?;
+
+        // create our image texture ready to be rendered
+        let (width, height) = img.dimensions();
+        let texture_size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let image = create_image_texture(device, (width, height));
+        let blurred_texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: texture_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            label: Some("blurred_texture"),
+            view_formats: Default::default(),
+        });
+
+        // create a sampler to tell the adapter how to handle the texture it's been given
+        let sampler = create_sampler(device, wgpu::FilterMode::Linear);
+
+        // create buffers to store our params and view in
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Params Buffer"),
+            contents: bytemuck::bytes_of(&Params::default()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let view_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("View Buffer"),
+            contents: bytemuck::bytes_of(&FULL_VIEW),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // describes what resources we want the shader to access by creating bindings
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("texture_bind_group_layout"),
+            entries: &[
+                // binding 0: texture
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+
+                // binding 1: sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+
+                // binding 2: uniform buffer (Params)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<Params>() as _
+                        ),
+                    },
+                    count: None,
+                },
+
+                // binding 3: uniform buffer (View)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<View>() as _
+                        ),
+                    },
+                    count: None,
+                },
+
+                // binding 4: blurred copy of the texture (--pre-blur)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+
+                // binding 5: where the effect applies (--mask)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let textures = Textures {
+            sampler,
+            blurred: blurred_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            mask: create_solid_texture(device, queue, "mask_texture", [255; 4]).create_view(&wgpu::TextureViewDescriptor::default()),
+        };
+        let bind_group = create_bind_group(device, &bind_group_layout, &image.1, &textures, &params_buffer, &view_buffer);
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(&VERTICES),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index Buffer"),
+            contents: bytemuck::cast_slice(INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let mut renderer = Self {
+            images: vec![image],
+            active_image: 0,
+            textures,
+            blur: Blur::new(device, wgpu::TextureFormat::Rgba8UnormSrgb),
+            pre_blur: 0.0,
+            blur_dirty: true,
+            params_buffer,
+            view_buffer,
+            bind_group_layout,
+            bind_groups: vec![bind_group],
+            filter: wgpu::FilterMode::Linear,
+            vertex_buffer,
+            index_buffer,
+            shader_module,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            previous: None,
+            backend,
+        };
+        renderer.write_image(queue, 0, img);
+
+        Ok(renderer)
+    }
+
+    // upload another image that can be switched to with `select_image` without going back to disk, it must be
+    // the same size as the first. returns its index
+    pub fn add_image(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, img: &RgbaImage) -> usize {
+        let image = create_image_texture(device, self.image_size());
+        self.bind_groups.push(create_bind_group(device, &self.bind_group_layout, &image.1, &self.textures, &self.params_buffer, &self.view_buffer));
+        self.images.push(image);
+        let index = self.images.len() - 1;
+        self.write_image(queue, index, img);
+        index
+    }
+
+    // draw the image at `index` from now on, the blurred copy gets redone for it
+    pub fn select_image(&mut self, index: usize) {
+        self.active_image = index;
+        self.blur_dirty = true;
+    }
+
+    pub fn active_image(&self) -> usize {
+        self.active_image
+    }
+
+    pub fn image_count(&self) -> usize {
+        self.images.len()
+    }
+
+    // write a new image to the texture at `index`, it must be the same size as the one we were created with
+    pub fn write_image(&mut self, queue: &wgpu::Queue, index: usize, img: &RgbaImage) {
+        self.blur_dirty |= index == self.active_image;
+        upload_rgba(queue, &self.images[index].0, img, self.backend);
+    }
+
+    // swap in a different shader, the pipelines get rebuilt the next time we draw
+    pub fn set_shader(&mut self, device: &wgpu::Device, shader_source: &str) -> Result<(), String> {
+        self.shader_module = create_shader_module(device, shader_source)?;
+        self.pipelines.clear();
+        self.previous = None;
+        Ok(())
+    }
+
+    // swap in a new version of the same shader, keeping the one it replaces for `draw_previous`. if the new
+    // one doesn't compile the current one stays
+    pub fn reload_shader(&mut self, device: &wgpu::Device, shader_source: &str) -> Result<(), String> {
+        let module = create_shader_module(device, shader_source)?;
+        let old_module = std::mem::replace(&mut self.shader_module, module);
+        let old_pipelines = std::mem::take(&mut self.pipelines);
+        self.previous = Some((old_module, old_pipelines));
+        Ok(())
+    }
+
+    // whether there's a shader from before a reload to compare against
+    pub fn has_previous(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    // draw the image with the shader from before the last reload onto `target`, clearing it first
+    pub fn draw_previous(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView, format: wgpu::TextureFormat) {
+        let Some((module, pipelines)) = self.previous.take() else {
+            return;
+        };
+        // draw as normal with the old shader swapped in, then put both back where they were
+        let current = (std::mem::replace(&mut self.shader_module, module), std::mem::replace(&mut self.pipelines, pipelines));
+        self.draw(device, encoder, target, format, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), None);
+        let previous = (std::mem::replace(&mut self.shader_module, current.0), std::mem::replace(&mut self.pipelines, current.1));
+        self.previous = Some(previous);
+    }
+
+    // radius in texels of the blurred copy of the image at binding 4
+    pub fn set_pre_blur(&mut self, radius: f32) {
+        self.pre_blur = radius;
+        self.blur_dirty = true;
+    }
+
+    // how many times the blur at binding 4 is run, more gives a wider, smoother blurred copy but costs more
+    pub fn set_blur_iterations(&mut self, iterations: u32) {
+        self.blur.iterations = iterations;
+        self.blur_dirty = true;
+    }
+
+    // switch between smooth (linear) and crisp (nearest) filtering of the image, which needs a new sampler
+    // and so a new bind group
+    pub fn set_filter(&mut self, device: &wgpu::Device, filter: wgpu::FilterMode) {
+        self.textures.sampler = create_sampler(device, filter);
+        self.filter = filter;
+        self.rebuild_bind_groups(device);
+    }
+
+    // limit the effect to the white parts of `mask`, which is stretched over the image. only the red channel
+    // is used so greyscale masks work as you'd expect
+    pub fn set_mask(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, mask: &RgbaImage) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("mask_texture"),
+            size: wgpu::Extent3d { width: mask.width(), height: mask.height(), depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: Default::default(),
+        });
+        upload_rgba(queue, &texture, mask, self.backend);
+        self.textures.mask = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.rebuild_bind_groups(device);
+    }
+
+    fn rebuild_bind_groups(&mut self, device: &wgpu::Device) {
+        self.bind_groups = self.images.iter()
+            .map(|(_, image)| create_bind_group(device, &self.bind_group_layout, image, &self.textures, &self.params_buffer, &self.view_buffer))
+            .collect();
+    }
+
+    pub fn filter(&self) -> wgpu::FilterMode {
+        self.filter
+    }
+
+    pub fn image_size(&self) -> (u32, u32) {
+        let texture = &self.images[0].0;
+        (texture.width(), texture.height())
+    }
+
+    pub fn write_params(&self, queue: &wgpu::Queue, params: &Params) {
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(params));
+    }
+
+    pub fn write_view(&self, queue: &wgpu::Queue, view: &View) {
+        queue.write_buffer(&self.view_buffer, 0, bytemuck::bytes_of(view));
+    }
+
+    // build and use every pipeline the window might draw to `format` with, on a throwaway 1x1 target, so the
+    // shader compile (which drivers often put off until first use) happens now rather than as a hitch on
+    // the first frame. it's only submitted, not waited on, so a shader reload doesn't freeze the window while
+    // the driver compiles, the next frame just queues up behind it
+    pub fn warm_up(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat, debug: bool) {
+        let mut target = ScratchTarget::new(format);
+        let mut debug_target = ScratchTarget::new(format);
+        let target = target.view(device, (1, 1));
+        let debug_target = debug.then(|| debug_target.view(device, (1, 1)));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Warm Up Encoder") });
+        for load in [wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), wgpu::LoadOp::Load] {
+            self.draw(device, &mut encoder, target, format, load, debug_target);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        device.poll(wgpu::PollType::Poll).expect("Failed to poll the gpu");
+    }
+
+    // build the pipeline for drawing to `format` if this is the first time we've seen it, `alpha_blend`
+    // composites over what's already there instead of replacing it, and `debug` uses the shader's
+    // `fs_debug` entry point which also writes to a second target of the same format
+    fn ensure_pipeline(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat, alpha_blend: bool, debug: bool) {
+        self.pipelines.entry((format, alpha_blend, debug)).or_insert_with(|| {
+            let color_target = wgpu::ColorTargetState {
+                format,
+                blend: Some(if alpha_blend { wgpu::BlendState::ALPHA_BLENDING } else { wgpu::BlendState::REPLACE }),
+                write_mask: wgpu::ColorWrites::ALL,
+            };
+            let debug_target = wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            };
+            let targets = [Some(color_target), Some(debug_target)];
+
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Render Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader_module,
+                    entry_point: Option::from("vs_main"),
+                    compilation_options: Default::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &[
+                            wgpu::VertexAttribute {
+                                offset: 0,
+                                shader_location: 0,
+                                format: wgpu::VertexFormat::Float32x2,
+                            },
+                            wgpu::VertexAttribute {
+                                offset: 8,
+                                shader_location: 1,
+                                format: wgpu::VertexFormat::Float32x2,
+                            },
+                        ],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader_module,
+                    entry_point: Option::from(if debug { "fs_debug" } else { "fs_main" }),
+                    compilation_options: Default::default(),
+                    targets: if debug { &targets } else { &targets[..1] },
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        });
+    }
+
+    // draw the shaded image onto `target`, either clearing it first or blending over what's already there,
+    // and if there's a `debug_target` write the shader's debug output to it too
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        load: wgpu::LoadOp<wgpu::Color>,
+        debug_target: Option<&wgpu::TextureView>,
+    ) {
+        // bring the blurred copy up to date first
+        if self.blur_dirty {
+            let size = self.image_size();
+            self.blur.run(device, encoder, &self.images[self.active_image].1, &self.textures.blurred, size, self.pre_blur);
+            self.blur_dirty = false;
+        }
+
+        let alpha_blend = load == wgpu::LoadOp::Load;
+        let debug = debug_target.is_some();
+        self.ensure_pipeline(device, format, alpha_blend, debug);
+        let pipeline = &self.pipelines[&(format, alpha_blend, debug)];
+
+        let color_attachment = wgpu::RenderPassColorAttachment {
+            view: target,
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        };
+        let debug_attachment = debug_target.map(|view| wgpu::RenderPassColorAttachment {
+            view,
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        });
+        let attachments = [Some(color_attachment), debug_attachment];
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: if debug { &attachments } else { &attachments[..1] },
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &self.bind_groups[self.active_image], &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..6, 0, 0..1);
+    }
+}
+
+// a plain render target that's replaced whenever it's asked for at a different size, reusing old sizes from a pool
+pub struct ScratchTarget {
+    format: wgpu::TextureFormat,
+    usage: wgpu::TextureUsages,
+    target: Option<(wgpu::Texture, wgpu::TextureView)>,
+    pool: TexturePool,
+}
+
+impl ScratchTarget {
+    pub fn new(format: wgpu::TextureFormat) -> Self {
+        Self::with_usage(format, wgpu::TextureUsages::empty())
+    }
+
+    // a target that can also be used in other ways, like being sampled afterwards
+    pub fn with_usage(format: wgpu::TextureFormat, usage: wgpu::TextureUsages) -> Self {
+        Self { format, usage: usage | wgpu::TextureUsages::RENDER_ATTACHMENT, target: None, pool: TexturePool::default() }
+    }
+
+    pub fn view(&mut self, device: &wgpu::Device, size: (u32, u32)) -> &wgpu::TextureView {
+        let (width, height) = size;
+        let stale = match &self.target {
+            Some((texture, _)) => texture.width() != width || texture.height() != height,
+            None => true,
+        };
+
+        if stale {
+            if let Some((old, _)) = self.target.take() {
+                self.pool.give(old);
+            }
+            let texture = self.pool.take(device, "scratch_target", self.format, size, self.usage);
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.target = Some((texture, view));
+        }
+
+        &self.target.as_ref().unwrap().1
+    }
+}
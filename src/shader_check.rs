@@ -0,0 +1,113 @@
+// a uniform the app binds, as (binding, rust struct name, rust struct size)
+pub type UniformBinding = (u32, &'static str, usize);
+
+// parse and validate the shader up front, so a broken custom shader gives a readable error
+// instead of wgpu panicking when the pipeline is built
+pub fn validate(source: &str) -> Result<(), String> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all());
+    validator.validate(&module).map_err(|e| e.emit_to_string(source))?;
+    Ok(())
+}
+
+// whether the shader has an entry point called `name`, a shader that doesn't parse passes so that
+// validate can report the real problem
+pub fn has_entry_point(source: &str, name: &str) -> bool {
+    naga::front::wgsl::parse_str(source).map_or(true, |module| module.entry_points.iter().any(|ep| ep.name == name))
+}
+
+// check each uniform the shader declares in group 0 is the same size as the rust struct we bind to it,
+// so adding a field on one side but not the other fails loudly instead of silently breaking rendering
+pub fn check_uniform_sizes(source: &str, uniforms: &[UniformBinding]) -> Result<(), String> {
+
+    // if the shader doesn't parse, leave it to validate to report the error properly
+    let Ok(module) = naga::front::wgsl::parse_str(source) else {
+        return Ok(());
+    };
+
+    for (_, var) in module.global_variables.iter() {
+        if var.space != naga::AddressSpace::Uniform {
+            continue;
+        }
+        let Some(binding) = &var.binding else {
+            continue;
+        };
+        if binding.group != 0 {
+            continue;
+        }
+
+        let shader_size = module.types[var.ty].inner.size(module.to_ctx()) as usize;
+        let var_name = var.name.as_deref().unwrap_or("<unnamed>");
+        match uniforms.iter().find(|(b, _, _)| *b == binding.binding) {
+            Some((_, name, size)) if *size != shader_size => {
+                return Err(format!(
+                    "uniform `{}` at binding {} is {} bytes in the shader but the Rust `{}` struct is {} bytes",
+                    var_name, binding.binding, shader_size, name, size,
+                ));
+            }
+            Some(_) => {}
+            None => {
+                return Err(format!(
+                    "uniform `{}` at binding {} isn't provided by the app",
+                    var_name, binding.binding,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// playback settings a shader can recommend for itself with `//@loop <seconds>` and `//@speed <multiplier>`
+// comments, so it looks right without the right command line
+#[derive(Debug, Default)]
+pub struct Directives {
+    pub loop_time: Option<f32>,
+    pub speed: Option<f32>,
+}
+
+// read the directives from a shader, anything that isn't understood is skipped with a warning
+pub fn directives(source: &str) -> Directives {
+    let mut directives = Directives::default();
+    for (number, line) in source.lines().enumerate() {
+        let Some(directive) = line.trim().strip_prefix("//@") else {
+            continue;
+        };
+        let mut words = directive.split_whitespace();
+        let (name, value) = (words.next().unwrap_or(""), words.next().and_then(|value| value.parse::<f32>().ok()));
+        match (name, value) {
+            ("loop", Some(value)) if value > 0.0 => directives.loop_time = Some(value),
+            ("speed", Some(value)) if value > 0.0 => directives.speed = Some(value),
+            _ => eprintln!("Ignoring shader directive on line {}: {}", number + 1, line.trim()),
+        }
+    }
+    directives
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_valid_directives() {
+        let found = directives("//@loop 4\n  //@speed 0.5 \nfn main() {}\n");
+        assert_eq!((found.loop_time, found.speed), (Some(4.0), Some(0.5)));
+    }
+
+    #[test]
+    fn skips_invalid_directives() {
+        let found = directives("//@loop -1\n//@loop\n//@speed fast\n//@zoom 2\n//@\n");
+        assert_eq!((found.loop_time, found.speed), (None, None));
+
+        // a bad one later on doesn't undo a good one
+        let found = directives("//@loop 3\n//@loop 0\n");
+        assert_eq!(found.loop_time, Some(3.0));
+    }
+
+    #[test]
+    fn defaults_without_directives() {
+        let found = directives("// loop 4\n// @speed 2\nfn main() {}\n");
+        assert_eq!((found.loop_time, found.speed), (None, None));
+        assert_eq!(directives("").loop_time, None);
+    }
+}
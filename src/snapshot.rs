@@ -0,0 +1,159 @@
+use crate::preset;
+use crate::render::Params;
+
+// number keys 1-9, one slot each
+pub const SLOTS: usize = 9;
+
+// tuned params stored in numbered slots for flipping between looks, kept in a small json file between runs
+#[derive(Default)]
+pub struct Snapshots {
+    slots: [Option<Params>; SLOTS],
+    // only write the file back if something was stored
+    changed: bool,
+}
+
+impl Snapshots {
+    // the snapshots saved last time, or none if there's no file yet
+    pub fn load(path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source).map_err(|e| format!("{}: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", path, e)),
+        }
+    }
+
+    // read the json written by `save`: an object of slot numbers, each an object of param names to numbers
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let body = source.trim().strip_prefix('{').and_then(|s| s.strip_suffix('}')).ok_or("expected a json object")?;
+        let mut snapshots = Self::default();
+        let mut rest = body.trim();
+        while !rest.is_empty() {
+            let (key, after) = rest.split_once(':').ok_or("expected \"slot\": {...}")?;
+            let slot: usize = key.trim().trim_matches('"').parse().map_err(|_| format!("invalid slot {}", key.trim()))?;
+            if !(1..=SLOTS).contains(&slot) {
+                return Err(format!("slot {} isn't between 1 and {}", slot, SLOTS));
+            }
+            let after = after.trim_start().strip_prefix('{').ok_or(format!("slot {}: expected an object", slot))?;
+            let (fields, after) = after.split_once('}').ok_or(format!("slot {}: missing }}", slot))?;
+
+            // anything the file doesn't mention keeps its default
+            let mut params = Params::default();
+            for field in fields.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+                let (name, value) = field.split_once(':').ok_or(format!("slot {}: expected \"name\": value", slot))?;
+                let (name, value) = (name.trim().trim_matches('"'), value.trim());
+                let invalid = || format!("slot {}: invalid value for {}: {}", slot, name, value);
+                if name == "component_mask" {
+                    params.component_mask = value.parse().map_err(|_| invalid())?;
+                    continue;
+                }
+                let value_slot = params.value_mut(name).ok_or(format!("slot {}: unknown param {}", slot, name))?;
+                *value_slot = value.parse().map_err(|_| invalid())?;
+            }
+            snapshots.slots[slot - 1] = Some(params);
+            rest = after.trim_start().trim_start_matches(',').trim_start();
+        }
+        Ok(snapshots)
+    }
+
+    // keep `params` in slot `index` (counting from 0)
+    pub fn store(&mut self, index: usize, params: &Params) {
+        self.slots[index] = Some(*params);
+        self.changed = true;
+    }
+
+    // the tuned values from slot `index` over the top of `params`, leaving time and the like alone.
+    // false if the slot is empty
+    pub fn recall(&self, index: usize, params: &mut Params) -> bool {
+        let Some(stored) = &self.slots[index] else {
+            return false;
+        };
+        for (name, value) in preset::tuned_values(stored) {
+            if let Some(slot) = params.value_mut(name) {
+                *slot = value;
+            }
+        }
+        params.component_mask = stored.component_mask;
+        true
+    }
+
+    // which slots have something in them, counting from 1
+    pub fn filled(&self) -> Vec<usize> {
+        (1..=SLOTS).filter(|slot| self.slots[slot - 1].is_some()).collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        let slots: Vec<String> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, params)| {
+                let params = params.as_ref()?;
+                let mut fields: Vec<String> = preset::tuned_values(params).map(|(name, value)| format!("    \"{}\": {:?}", name, value)).collect();
+                fields.push(format!("    \"component_mask\": {}", params.component_mask));
+                Some(format!("  \"{}\": {{\n{}\n  }}", index + 1, fields.join(",\n")))
+            })
+            .collect();
+        format!("{{\n{}\n}}\n", slots.join(",\n"))
+    }
+
+    // write the slots to `path` if any were stored this session, true if it was written
+    pub fn save(&self, path: &str) -> std::io::Result<bool> {
+        if !self.changed {
+            return Ok(false);
+        }
+        std::fs::write(path, self.to_json())?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut params = Params { bloom_fac: 0.25, crt_curvature: 0.5, component_mask: 5, ..Params::default() };
+        let mut snapshots = Snapshots::default();
+        snapshots.store(2, &params);
+        params.bloom_fac = 1.5;
+        snapshots.store(8, &params);
+
+        let parsed = Snapshots::parse(&snapshots.to_json()).unwrap();
+        assert_eq!(parsed.filled(), [3, 9]);
+        assert_eq!(parsed.to_json(), snapshots.to_json());
+
+        let mut recalled = Params::default();
+        assert!(parsed.recall(2, &mut recalled));
+        assert_eq!((recalled.bloom_fac, recalled.crt_curvature, recalled.component_mask), (0.25, 0.5, 5));
+        assert!(!parsed.recall(0, &mut recalled));
+    }
+
+    #[test]
+    fn missing_fields_keep_their_defaults() {
+        let parsed = Snapshots::parse(r#"{ "1": { "bloom_fac": 2.0 } }"#).unwrap();
+        let mut params = Params::default();
+        assert!(parsed.recall(0, &mut params));
+        assert_eq!(params.bloom_fac, 2.0);
+        assert_eq!(params.crt_curvature, Params::default().crt_curvature);
+        assert!(Snapshots::parse("{}").unwrap().filled().is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        for source in [
+            "",
+            "[]",
+            r#"{ "0": {} }"#,
+            r#"{ "10": {} }"#,
+            r#"{ "one": {} }"#,
+            r#"{ "1": 2 }"#,
+            r#"{ "1": { "bloom_fac": 2.0 "#,
+            r#"{ "1": { "bloom_fac" } }"#,
+            r#"{ "1": { "bloom_fac": "high" } }"#,
+            r#"{ "1": { "not_a_param": 1.0 } }"#,
+            r#"{ "1": { "component_mask": -1 } }"#,
+        ] {
+            assert!(Snapshots::parse(source).is_err(), "{:?} should fail", source);
+        }
+    }
+}
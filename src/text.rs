@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use wgpu::util::DeviceExt;
+
+// each glyph is 5x7 pixels, padded to a 6x8 cell so there's a gap between letters and lines
+pub const GLYPH_W: u32 = 6;
+pub const GLYPH_H: u32 = 8;
+
+// the printable ascii range, anything else is drawn as '?'
+const FIRST_CHAR: u8 = b' ';
+const LAST_CHAR: u8 = b'~';
+
+// the atlas has a solid cell after the glyphs, used for the box behind the text
+const SOLID_CELL: u32 = (LAST_CHAR - FIRST_CHAR + 1) as u32;
+const ATLAS_CELLS: u32 = SOLID_CELL + 1;
+
+// classic 5x7 font, one byte per column with the top row in the lowest bit
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x08, 0x2A, 0x1C, 0x2A, 0x08], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x00, 0x08, 0x14, 0x22, 0x41], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x41, 0x22, 0x14, 0x08, 0x00], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x01, 0x01], // F
+    [0x3E, 0x41, 0x41, 0x51, 0x32], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x04, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x7F, 0x20, 0x18, 0x20, 0x7F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x03, 0x04, 0x78, 0x04, 0x03], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x00, 0x7F, 0x41, 0x41], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // backslash
+    [0x41, 0x41, 0x7F, 0x00, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x08, 0x14, 0x54, 0x54, 0x3C], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x00, 0x7F, 0x10, 0x28, 0x44], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x10, 0x08, 0x08, 0x10, 0x08], // ~
+];
+
+// which corner of the target text is placed against
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+// some lines of text and where to put them
+pub struct TextBlock<'a> {
+    pub lines: &'a [String],
+    pub corner: Corner,
+    // size of each font pixel in target pixels
+    pub scale: f32,
+    pub color: [f32; 4],
+}
+
+// break `text` into lines that fit across `width` target pixels at `scale`, keeping at most `max_lines`
+// (the last one says how many were cut). for long messages like shader errors
+pub fn wrap(text: &str, width: u32, scale: f32, max_lines: usize) -> Vec<String> {
+    let columns = ((width as f32 - scale) / (GLYPH_W as f32 * scale)).max(1.0) as usize;
+    let mut lines: Vec<String> = text
+        .lines()
+        .flat_map(|line| {
+            let chars: Vec<char> = line.trim_end().chars().collect();
+            if chars.is_empty() {
+                return vec![String::new()];
+            }
+            chars.chunks(columns).map(|chunk| chunk.iter().collect()).collect()
+        })
+        .collect();
+
+    // always room for the note about what was cut
+    let max_lines = max_lines.max(1);
+    if lines.len() > max_lines {
+        let cut = lines.len() - max_lines + 1;
+        lines.truncate(max_lines - 1);
+        lines.push(format!("... {} more lines", cut));
+    }
+    lines
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+// bake the font into a single row atlas of coverage values
+fn bake_atlas() -> Vec<u8> {
+    let width = ATLAS_CELLS * GLYPH_W;
+    let mut pixels = vec![0u8; (width * GLYPH_H) as usize];
+
+    for (i, glyph) in FONT.iter().enumerate() {
+        for (col, bits) in glyph.iter().enumerate() {
+            for row in 0..7 {
+                if bits >> row & 1 == 1 {
+                    let x = i as u32 * GLYPH_W + col as u32;
+                    pixels[(row * width + x) as usize] = 255;
+                }
+            }
+        }
+    }
+
+    // fill the solid cell completely
+    for row in 0..GLYPH_H {
+        for col in 0..GLYPH_W {
+            pixels[(row * width + SOLID_CELL * GLYPH_W + col) as usize] = 255;
+        }
+    }
+
+    pixels
+}
+
+// lightweight text drawing for overlays, so they don't need the gui
+pub struct TextRenderer {
+    bind_group: wgpu::BindGroup,
+    shader_module: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+
+    // one pipeline per target format, created the first time we draw to that format
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl TextRenderer {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let atlas = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("font_atlas"),
+                size: wgpu::Extent3d { width: ATLAS_CELLS * GLYPH_W, height: GLYPH_H, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: Default::default(),
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &bake_atlas(),
+        );
+
+        // nearest filtering keeps the font crisp at any whole number scale
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("font_sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("text_bind_group_layout"),
+            entries: &[
+                // binding 0: font atlas
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+
+                // binding 1: sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let atlas_view = atlas.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/text.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            bind_group,
+            shader_module,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    // build the pipeline for drawing to `format` if this is the first time we've seen it
+    fn ensure_pipeline(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) {
+        self.pipelines.entry(format).or_insert_with(|| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Text Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader_module,
+                    entry_point: Option::from("vs_main"),
+                    compilation_options: Default::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader_module,
+                    entry_point: Option::from("fs_main"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        });
+    }
+
+    // draw `block` over whatever is already in `target`, with a dark box behind it so it's readable on anything
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        target_size: (u32, u32),
+        block: &TextBlock,
+    ) {
+        if block.lines.is_empty() {
+            return;
+        }
+
+        // size of the whole block in target pixels, with a one font pixel border
+        let cell_w = GLYPH_W as f32 * block.scale;
+        let cell_h = GLYPH_H as f32 * block.scale;
+        let columns = block.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let block_w = columns as f32 * cell_w + block.scale;
+        let block_h = block.lines.len() as f32 * cell_h + block.scale;
+
+        let (target_w, target_h) = (target_size.0 as f32, target_size.1 as f32);
+        let (left, top) = match block.corner {
+            Corner::TopLeft => (0.0, 0.0),
+            Corner::TopRight => (target_w - block_w, 0.0),
+            Corner::BottomLeft => (0.0, target_h - block_h),
+            Corner::BottomRight => (target_w - block_w, target_h - block_h),
+        };
+
+        // quad from pixel rect to clip space, using one atlas cell
+        let mut vertices = Vec::new();
+        let mut push_quad = |x: f32, y: f32, w: f32, h: f32, cell: u32, color: [f32; 4]| {
+            let (x0, x1) = (x / target_w * 2.0 - 1.0, (x + w) / target_w * 2.0 - 1.0);
+            let (y0, y1) = (1.0 - y / target_h * 2.0, 1.0 - (y + h) / target_h * 2.0);
+            let (u0, u1) = (cell as f32 / ATLAS_CELLS as f32, (cell + 1) as f32 / ATLAS_CELLS as f32);
+            for (px, py, u, v) in [(x0, y0, u0, 0.0), (x1, y0, u1, 0.0), (x1, y1, u1, 1.0), (x0, y0, u0, 0.0), (x1, y1, u1, 1.0), (x0, y1, u0, 1.0)] {
+                vertices.push(TextVertex { position: [px, py], uv: [u, v], color });
+            }
+        };
+
+        push_quad(left, top, block_w, block_h, SOLID_CELL, [0.0, 0.0, 0.0, 0.6 * block.color[3]]);
+        for (row, line) in block.lines.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                let code = if ch.is_ascii_graphic() || ch == ' ' { ch as u8 } else { b'?' };
+                if code == b' ' {
+                    continue;
+                }
+                let x = left + block.scale + col as f32 * cell_w;
+                let y = top + block.scale + row as f32 * cell_h;
+                push_quad(x, y, cell_w, cell_h, (code - FIRST_CHAR) as u32, block.color);
+            }
+        }
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Text Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        self.ensure_pipeline(device, format);
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Text Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipelines[&format]);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..vertices.len() as u32, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // wide enough for `columns` glyphs at scale 1
+    fn width(columns: u32) -> u32 {
+        columns * GLYPH_W + 1
+    }
+
+    #[test]
+    fn splits_long_words() {
+        assert_eq!(wrap("abcdefghij", width(4), 1.0, 10), ["abcd", "efgh", "ij"]);
+        // scale makes each glyph wider, so fewer fit
+        assert_eq!(wrap("abcdefghij", 2 * width(4), 2.0, 10), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("abcd", width(4), 1.0, 10), ["abcd"]);
+    }
+
+    #[test]
+    fn keeps_explicit_newlines() {
+        assert_eq!(wrap("one\n\ntwo  \r\nthree", width(10), 1.0, 10), ["one", "", "two", "three"]);
+        assert_eq!(wrap("", width(10), 1.0, 10), Vec::<String>::new());
+    }
+
+    #[test]
+    fn zero_width_still_makes_progress() {
+        assert_eq!(wrap("abc", 0, 1.0, 10), ["a", "b", "c"]);
+        assert_eq!(wrap("abc", 0, 0.0, 10), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn cuts_to_max_lines() {
+        assert_eq!(wrap("1\n2\n3\n4\n5", width(10), 1.0, 3), ["1", "2", "... 3 more lines"]);
+        assert_eq!(wrap("1\n2\n3", width(10), 1.0, 3), ["1", "2", "3"]);
+        assert_eq!(wrap("1\n2", width(10), 1.0, 0), ["... 2 more lines"]);
+    }
+}
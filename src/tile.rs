@@ -0,0 +1,209 @@
+use wgpu::util::DeviceExt;
+use crate::view::{self, ViewSettings};
+
+// the preview is this many copies across and this many down
+pub const TILES: u32 = 2;
+
+// uniforms for the tile shader, must match `Tile` in tile.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+struct TileUniform {
+    quad_scale: [f32; 2],
+    quad_offset: [f32; 2],
+    tiles: f32,
+    _pad: [f32; 3],
+}
+
+// shows the live render repeated in a grid so any seams along its edges stand out, for checking that
+// backgrounds tile
+pub struct Tile {
+    img_size: (u32, u32),
+    live_view: wgpu::TextureView,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+// the live render repeated with the same filtering as the image itself
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    live_view: &wgpu::TextureView,
+    buffer: &wgpu::Buffer,
+    filter: wgpu::FilterMode,
+) -> wgpu::BindGroup {
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("tile_sampler"),
+        address_mode_u: wgpu::AddressMode::Repeat,
+        address_mode_v: wgpu::AddressMode::Repeat,
+        mag_filter: filter,
+        min_filter: filter,
+        ..Default::default()
+    });
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("tile_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(live_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+impl Tile {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, img_size: (u32, u32), filter: wgpu::FilterMode) -> Self {
+        // the live render is drawn here at the image's own size each frame, then repeated across the quad
+        let live_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("tile_texture"),
+            size: wgpu::Extent3d { width: img_size.0, height: img_size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: Default::default(),
+        });
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Tile Buffer"),
+            contents: bytemuck::bytes_of(&<TileUniform as bytemuck::Zeroable>::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("tile_bind_group_layout"),
+            entries: &[
+                // binding 0: live render
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+
+                // binding 1: sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+
+                // binding 2: uniform buffer (Tile)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let live_view = live_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = create_bind_group(device, &bind_group_layout, &live_view, &buffer, filter);
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tile Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/tile.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tile Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // no vertex buffer, the shader makes the quad from the vertex index
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tile Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Option::from("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Option::from("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self { img_size, live_view, buffer, bind_group_layout, bind_group, pipeline }
+    }
+
+    // follow the image's linear or nearest filtering, which needs a new sampler and so a new bind group
+    pub fn set_filter(&mut self, device: &wgpu::Device, filter: wgpu::FilterMode) {
+        self.bind_group = create_bind_group(device, &self.bind_group_layout, &self.live_view, &self.buffer, filter);
+    }
+
+    // where the live render should be drawn each frame, with `render::FULL_VIEW`
+    pub fn live_view(&self) -> &wgpu::TextureView {
+        &self.live_view
+    }
+
+    // draw the grid over `target`, placed with `settings` as if it were one image `TILES` times the size
+    pub fn draw(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        window_size: (u32, u32),
+        settings: &mut ViewSettings,
+    ) {
+        // the whole grid is fitted, cropping to the artwork or leaving room for a bezel would hide the seams
+        let grid_size = (self.img_size.0 * TILES, self.img_size.1 * TILES);
+        let mut grid_settings = ViewSettings { bezel: 0, ..*settings };
+        let grid = view::compute_view(grid_size, window_size, None, &mut grid_settings);
+        settings.pan = grid_settings.pan;
+
+        let uniform = TileUniform { quad_scale: grid.quad_scale, quad_offset: grid.quad_offset, tiles: TILES as f32, _pad: [0.0; 3] };
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Tile Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
@@ -0,0 +1,115 @@
+// pixel rectangle as (x, y, width, height)
+pub type Bounds = (u32, u32, u32, u32);
+
+// where the quad sits in the window and which part of the texture it shows, must match `View` in the shader
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct View {
+    pub quad_scale: [f32; 2],
+    pub quad_offset: [f32; 2],
+    pub uv_offset: [f32; 2],
+    pub uv_scale: [f32; 2],
+    // rotation in radians and the window's aspect ratio, so the quad can turn without squashing
+    pub tilt: [f32; 2],
+}
+
+// everything the user can change about how the image is placed in the window
+#[derive(Debug)]
+pub struct ViewSettings {
+    // crop to the non-transparent artwork
+    pub auto_fit: bool,
+    // show texels 1:1 with screen pixels instead of filling the window
+    pub native: bool,
+    // only ever scale by whole numbers, letterboxing whatever's left over
+    pub integer_scale: bool,
+    pub zoom: f32,
+    // offset from centre in window pixels
+    pub pan: [f32; 2],
+    // width of the frame around the card in image pixels, room is left for it when fitting the card
+    pub bezel: u32,
+}
+
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 64.0;
+
+impl ViewSettings {
+    // zoom in or out by a number of scroll steps
+    pub fn zoom_by(&mut self, steps: f32) {
+        self.zoom = (self.zoom * 1.1_f32.powf(steps)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+// work out the view for the current window, `bounds` is the non-transparent area of the image if there is one
+pub fn compute_view(img_size: (u32, u32), window_size: (u32, u32), bounds: Option<Bounds>, settings: &mut ViewSettings) -> View {
+    let (x, y, w, h) = match bounds {
+        Some(bounds) if settings.auto_fit => bounds,
+        _ => (0, 0, img_size.0, img_size.1),
+    };
+    let (win_w, win_h) = (window_size.0 as f32, window_size.1 as f32);
+
+    // everything below places the card plus its bezel, the card is inset at the end
+    let (card_w, card_h) = (w, h);
+    let (w, h) = (w + 2 * settings.bezel, h + 2 * settings.bezel);
+
+    // size of the quad in window pixels
+    let (quad_w, quad_h) = if settings.native {
+        (w as f32 * settings.zoom, h as f32 * settings.zoom)
+    } else if settings.integer_scale {
+        // biggest whole multiple that fits, but never smaller than 1:1
+        let fit = (win_w / w as f32).min(win_h / h as f32).floor();
+        let scale = (fit * settings.zoom).round().max(1.0);
+        (w as f32 * scale, h as f32 * scale)
+    } else if settings.auto_fit {
+        // letterbox the cropped region so it keeps its aspect ratio
+        let scale = (win_w / w as f32).min(win_h / h as f32) * settings.zoom;
+        (w as f32 * scale, h as f32 * scale)
+    } else {
+        // stretch the whole image over the window like before
+        (win_w * settings.zoom, win_h * settings.zoom)
+    };
+
+    // only allow panning as far as the edges of the image, so an image smaller than the window stays centred
+    let max_pan_x = ((quad_w - win_w) / 2.0).max(0.0);
+    let max_pan_y = ((quad_h - win_h) / 2.0).max(0.0);
+    settings.pan[0] = settings.pan[0].clamp(-max_pan_x, max_pan_x);
+    settings.pan[1] = settings.pan[1].clamp(-max_pan_y, max_pan_y);
+
+    // top left corner of the quad in window pixels, snapped to whole pixels in native and integer scale
+    // modes so texels line up with screen pixels instead of landing halfway between them
+    let mut left = (win_w - quad_w) / 2.0 + settings.pan[0];
+    let mut top = (win_h - quad_h) / 2.0 + settings.pan[1];
+    if settings.native || settings.integer_scale {
+        left = left.round();
+        top = top.round();
+    }
+
+    // inset the card inside its bezel, this stays on whole pixels in native and integer scale modes
+    let inset_x = settings.bezel as f32 * quad_w / w as f32;
+    let inset_y = settings.bezel as f32 * quad_h / h as f32;
+    let (left, top) = (left + inset_x, top + inset_y);
+    let (quad_w, quad_h) = (quad_w - 2.0 * inset_x, quad_h - 2.0 * inset_y);
+
+    // convert to clip space, where the window spans -1 to 1 and y points up
+    View {
+        quad_scale: [quad_w / win_w, quad_h / win_h],
+        quad_offset: [
+            (left + quad_w / 2.0) / win_w * 2.0 - 1.0,
+            1.0 - (top + quad_h / 2.0) / win_h * 2.0,
+        ],
+        uv_offset: [x as f32 / img_size.0 as f32, y as f32 / img_size.1 as f32],
+        uv_scale: [card_w as f32 / img_size.0 as f32, card_h as f32 / img_size.1 as f32],
+        tilt: [0.0, win_w / win_h],
+    }
+}
+
+// the view for something around a card drawn with `card`, reaching `by` image pixels past it on every side
+// (the bezel, the glow)
+pub fn grow_view(card: &View, img_size: (u32, u32), by: u32) -> View {
+    let card_w = card.uv_scale[0] * img_size.0 as f32;
+    let card_h = card.uv_scale[1] * img_size.1 as f32;
+    let grow = [(card_w + 2.0 * by as f32) / card_w, (card_h + 2.0 * by as f32) / card_h];
+    View {
+        quad_scale: [card.quad_scale[0] * grow[0], card.quad_scale[1] * grow[1]],
+        ..*card
+    }
+}